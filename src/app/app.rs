@@ -1,15 +1,35 @@
-use crate::app::ui_component::{AutoCompleteEditor, ListTextEditor, TableEditor, UIEventProcessor};
+use crate::app::external::{ExternalEdit, ExternalTarget};
+use crate::app::help::{self, Help};
+use crate::app::history::{Command, EditKind, Focus, History, SetField, TextEdit};
+use crate::app::keymap::{Action, Context, Keymap};
+use crate::app::layout::{self, RESIZE_STEP};
+use crate::app::modal::{Modal, Mode, NormalKey};
+use crate::app::search::{search, SearchEditor, SearchTarget};
+use crate::app::text_pane::{PaneText, TextPane};
+use crate::app::theme::Theme;
+use crate::app::todo_view::TodoView;
+use crate::app::ui_component::{
+    contains, AutoCompleteEditor, LineEditor, ListTextEditor, TableEditor, UIEventProcessor,
+    ViewRow,
+};
 use crate::app::{ActionPayload, Event};
+use crate::config::{Config, LayoutConfig};
+use crate::git::{self, Commit, RepoStatus};
+use crate::storage::{migrations, CorruptStore, Storage, Workspace, Workspaces, SCHEMA_VERSION};
 use crate::todo::todo::{EditableRowItem, EditableStateItem};
-use crate::todo::todo::{Note, Project, SelectableItem, TodoItem};
+use crate::todo::todo::{Note, Priority, Project, Status, TodoItem};
 use crate::util::TabsState;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use chrono::{Local, NaiveDate, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent};
+use failure::Error;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::cell::RefCell;
-use std::error::Error;
-use std::fs::File;
-use std::io::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 struct Person {
     name: String,
@@ -19,42 +39,74 @@ struct Person {
 
 use std::sync::mpsc::Sender;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum CodePalAction {
     AddToDoItem,
     AddNote,
+    EditNote,
+    EditDescription,
     AddProject,
     SelectProject,
+    SelectWorkspace,
+    Filter,
+    Due,
+    Branch,
+    Search,
+    ConfirmQuit,
+    Help,
     None,
 }
 
+impl CodePalAction {
+    /// What the user is doing, as the status bar shows it.
+    pub fn label(&self) -> &'static str {
+        match self {
+            CodePalAction::AddToDoItem => "editing todos",
+            CodePalAction::AddNote => "editing notes",
+            CodePalAction::EditNote => "editing a note",
+            CodePalAction::EditDescription => "editing a description",
+            CodePalAction::AddProject => "editing projects",
+            CodePalAction::SelectProject => "picking a project",
+            CodePalAction::SelectWorkspace => "switching workspaces",
+            CodePalAction::Filter => "filtering",
+            CodePalAction::Due => "setting a due date",
+            CodePalAction::Branch => "naming a branch",
+            CodePalAction::Search => "searching",
+            CodePalAction::ConfirmQuit => "quitting",
+            CodePalAction::Help => "reading the help",
+            CodePalAction::None => "browsing",
+        }
+    }
+}
+
+/// The commits on a branch, `None` if it doesn't exist yet, or why they
+/// couldn't be read.
+type BranchCommits = Result<Option<Vec<Commit>>, String>;
+
+/// How long a message stays in the status bar.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+/// How often the repositories of projects are looked at while shown.
+const REPO_REFRESH: Duration = Duration::from_secs(5);
+
 #[derive(Serialize, Deserialize)]
 pub struct AppState {
+    pub schema_version: u64,
     pub todo_items: Rc<RefCell<Vec<TodoItem>>>,
-    #[serde(default = "default_projects")]
     pub projects: Rc<RefCell<Vec<Project>>>,
 }
 
-fn default_projects() -> Rc<RefCell<Vec<Project>>> {
-    Rc::new(RefCell::new(vec![Project::new(vec![
-        String::from(""),
-        String::from(""),
-        String::from(""),
-    ])]))
-}
-
 impl AppState {
-    pub fn new() -> AppState {
-        let mut pathbuf = dirs::home_dir().unwrap();
-        pathbuf.push("code_pal.json");
-        let path = pathbuf.as_path();
-        if path.exists() {
-            let json_file = File::open(path).expect("file not found");
-            let app_state: AppState =
-                serde_json::from_reader(json_file).expect("error while reading json");
-            return app_state;
-        }
+    /// Parses a store of any schema version, upgrading it to the current one.
+    pub fn from_json(path: &Path, json: &str) -> Result<AppState, Error> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| Error::from(CorruptStore::new(path, &e)))?;
+        let value = migrations::migrate(value)?;
+        serde_json::from_value(value).map_err(|e| CorruptStore::new(path, &e).into())
+    }
+
+    pub fn empty() -> AppState {
         AppState {
+            schema_version: SCHEMA_VERSION,
             todo_items: Rc::new(RefCell::new(vec![TodoItem::new(String::from(""))])),
             projects: Rc::new(RefCell::new(vec![Project::new(vec![
                 String::from(""),
@@ -63,44 +115,104 @@ impl AppState {
             ])])),
         }
     }
-    pub fn save(&mut self) {
-        let serialized = serde_json::to_string(&self).unwrap();
-
-        let mut pathbuf = dirs::home_dir().unwrap();
-        pathbuf.push("code_pal.json");
-        let path = pathbuf.as_path();
-        let display = path.display();
-
-        // Open a file in write-only mode, returns `io::Result<File>`
-        let mut file = match File::create(&path) {
-            Err(why) => panic!("couldn't create {}: {}", display, why.description()),
-            Ok(file) => file,
-        };
-
-        // Write the `LOREM_IPSUM` string to `file`, returns `io::Result<()>`
-        match file.write_all(serialized.as_bytes()) {
-            Err(why) => panic!("couldn't write to {}: {}", display, why.description()),
-            Ok(_) => {}
-        }
-    }
 }
 
 pub struct App<'a> {
     pub app_state: AppState,
+    pub store: Box<dyn Storage>,
+    pub workspaces: Workspaces,
+    pub workspace: String,
     pub todo_items: ListTextEditor<TodoItem>,
     pub notes: ListTextEditor<Note>,
+    pub note_pane: TextPane,
+    pub description_pane: TextPane,
     pub projects: TableEditor<Project>,
     pub todo_item_project: AutoCompleteEditor<Project>,
+    pub workspace_switcher: AutoCompleteEditor<Workspace>,
+    pub todo_filter: LineEditor,
+    pub due_editor: LineEditor,
+    pub branch_editor: LineEditor,
+    pub todo_view: TodoView,
+    pub search: SearchEditor,
+    pub help: Help,
     pub current_action: CodePalAction,
     pub title: &'a str,
     pub should_quit: bool,
+    pub dirty: bool,
+    pub last_edit: Option<Instant>,
+    pub autosave_delay: Option<Duration>,
+    pub message: Option<String>,
+    /// The message the status bar shows and since when.
+    message_shown: Option<(String, Instant)>,
+    pub history: History,
+    pub keymap: Keymap,
+    pub theme: Theme,
+    pub layout: LayoutConfig,
+    /// Where a resized layout is saved, if anywhere.
+    pub config_path: Option<PathBuf>,
+    /// Set when the lists are edited in vim-like modes.
+    pub modal: Option<Modal>,
+    /// Text waiting to be edited in `$EDITOR`, picked up by the main loop.
+    pub external_edit: Option<ExternalEdit>,
+    /// What the directories of projects look like as git repositories, by
+    /// project identifier.
+    pub repo_statuses: HashMap<String, Result<RepoStatus, String>>,
+    /// When the repositories were last looked at, and whether that is still
+    /// going on in the background.
+    repos_checked: Option<Instant>,
+    checking_repos: bool,
+    /// The commits on the branches of todos, by directory and branch name.
+    pub branch_commits: HashMap<(String, String), BranchCommits>,
+    /// The branch last looked at and when, and whether that is still
+    /// going on in the background.
+    branch_checked: Option<((String, String), Instant)>,
+    checking_branch: bool,
+    sender: Sender<Event>,
     pub tabs: TabsState<'a>,
 }
 
+fn todo_project(todo: &mut TodoItem) -> &mut String {
+    &mut todo.project_identifier
+}
+
+fn todo_status(todo: &mut TodoItem) -> &mut Status {
+    &mut todo.status
+}
+
+fn todo_priority(todo: &mut TodoItem) -> &mut Priority {
+    &mut todo.priority
+}
+
+fn todo_due(todo: &mut TodoItem) -> &mut Option<NaiveDate> {
+    &mut todo.due
+}
+
+fn todo_branch(todo: &mut TodoItem) -> &mut Option<String> {
+    &mut todo.branch
+}
+
+fn todo_description(todo: &mut TodoItem) -> &mut Vec<String> {
+    &mut todo.description
+}
+
+fn note_content(note: &mut Note, _: usize) -> &mut String {
+    &mut note.content
+}
+
 impl<'a> App<'a> {
-    pub fn new(title: &'a str, app_state: AppState, sender: Sender<Event>) -> App<'a> {
+    pub fn new(
+        title: &'a str,
+        app_state: AppState,
+        store: Box<dyn Storage>,
+        workspaces: Workspaces,
+        workspace: String,
+        sender: Sender<Event>,
+    ) -> App<'a> {
         let mut a = App {
             app_state,
+            store,
+            workspaces,
+            workspace,
             title,
             todo_items: ListTextEditor::new(
                 String::from("Todo Items"),
@@ -112,6 +224,8 @@ impl<'a> App<'a> {
                 None::<Rc<RefCell<Vec<Note>>>>,
                 Sender::clone(&sender),
             ),
+            note_pane: TextPane::new(String::from("Note")),
+            description_pane: TextPane::new(String::from("Description")),
             projects: TableEditor::new(
                 String::from("Projects"),
                 None::<Rc<RefCell<Vec<Project>>>>,
@@ -120,18 +234,49 @@ impl<'a> App<'a> {
                     String::from("url"),
                     String::from("Directory"),
                 ],
-                vec![20, 50, 50],
+                LayoutConfig::default().project_columns,
                 Sender::clone(&sender),
             ),
             todo_item_project: AutoCompleteEditor::new(
                 String::from("Project"),
                 String::from(""),
                 vec![],
-                sender,
+                Sender::clone(&sender),
             ),
+            workspace_switcher: AutoCompleteEditor::new(
+                String::from("Workspace"),
+                String::from(""),
+                vec![],
+                Sender::clone(&sender),
+            ),
+            todo_filter: LineEditor::new(String::from("Filter"), Sender::clone(&sender)),
+            due_editor: LineEditor::new(String::from("Due date"), Sender::clone(&sender)),
+            branch_editor: LineEditor::new(String::from("Branch"), Sender::clone(&sender)),
+            search: SearchEditor::new(String::from("Search"), Sender::clone(&sender)),
+            help: Help::new(String::from("Keys (esc to close)")),
+            todo_view: TodoView::default(),
             current_action: CodePalAction::None,
             should_quit: false,
-            tabs: TabsState::new(vec!["Notes", "Projects"]),
+            dirty: false,
+            last_edit: None,
+            autosave_delay: Some(Duration::from_secs(2)),
+            message: None,
+            message_shown: None,
+            history: History::new(),
+            keymap: Keymap::default(),
+            theme: Theme::default(),
+            layout: LayoutConfig::default(),
+            config_path: None,
+            modal: None,
+            external_edit: None,
+            repo_statuses: HashMap::new(),
+            repos_checked: None,
+            checking_repos: false,
+            branch_commits: HashMap::new(),
+            branch_checked: None,
+            checking_branch: false,
+            sender,
+            tabs: TabsState::new(vec!["Notes", "Projects", "Workspaces"]),
         };
         a.init_state();
         a
@@ -141,12 +286,11 @@ impl<'a> App<'a> {
         self.todo_items.current_text = Some(self.app_state.todo_items.clone());
         self.projects.current_text = Some(self.app_state.projects.clone());
         self.set_notes();
+        self.set_workspace_suggestions();
+        self.refresh_todo_view();
+        self.refresh_text_panes();
     }
 
-    pub fn on_up(&mut self) {}
-
-    pub fn on_down(&mut self) {}
-
     pub fn on_right(&mut self) {
         self.tabs.next();
     }
@@ -159,12 +303,41 @@ impl<'a> App<'a> {
         self.on_stop_action();
         self.current_action = CodePalAction::AddToDoItem;
         self.todo_items.on_activate();
+        self.set_mode(Mode::Normal);
     }
 
     pub fn on_add_note(&mut self) {
         self.on_stop_action();
         self.current_action = CodePalAction::AddNote;
         self.notes.on_activate();
+        self.set_mode(Mode::Normal);
+    }
+
+    /// Opens the selected note, or the first one, in the note pane.
+    pub fn on_edit_note(&mut self) {
+        if self.notes.current_text.is_none() {
+            return;
+        }
+        self.on_stop_action();
+        self.tabs.index = 0;
+        if self.notes.current_selection.is_none() {
+            self.select_note(0);
+        }
+        self.refresh_text_panes();
+        self.current_action = CodePalAction::EditNote;
+        self.note_pane.on_activate();
+    }
+
+    /// Edits the description of the selected todo.
+    pub fn on_edit_description(&mut self) {
+        if self.todo_items.current_selection.is_none() {
+            return;
+        }
+        self.on_stop_action();
+        self.tabs.index = 0;
+        self.refresh_text_panes();
+        self.current_action = CodePalAction::EditDescription;
+        self.description_pane.on_activate();
     }
 
     pub fn on_add_project(&mut self) {
@@ -179,6 +352,244 @@ impl<'a> App<'a> {
         self.todo_item_project.on_activate();
     }
 
+    pub fn on_select_workspace(&mut self) {
+        self.on_stop_action();
+        self.tabs.index = 2;
+        self.current_action = CodePalAction::SelectWorkspace;
+        self.workspace_switcher.text = String::from("");
+        self.set_workspace_suggestions();
+        self.workspace_switcher.on_activate();
+    }
+
+    pub fn on_filter(&mut self) {
+        self.on_stop_action();
+        self.tabs.index = 0;
+        self.current_action = CodePalAction::Filter;
+        self.todo_filter.on_activate();
+    }
+
+    /// Asks for the due date of the selected todo.
+    pub fn on_set_due(&mut self) {
+        let index = match self.todo_items.current_selection {
+            Some(index) => index,
+            None => return,
+        };
+        let due = match self.app_state.todo_items.borrow().get(index) {
+            Some(todo) => todo.due.map(|due| due.to_string()).unwrap_or_default(),
+            None => return,
+        };
+        self.on_stop_action();
+        self.tabs.index = 0;
+        self.current_action = CodePalAction::Due;
+        self.due_editor.text = due;
+        self.due_editor.on_activate();
+    }
+
+    /// Sets the due date of the selected todo to the date typed, keeps
+    /// asking while it isn't one.
+    pub fn set_due(&mut self, text: &str) {
+        let due = match TodoItem::parse_due(text) {
+            Ok(due) => due,
+            Err(_) => {
+                self.message = Some(format!("{:?} isn't a date like 2020-02-14", text.trim()));
+                return;
+            }
+        };
+        self.on_stop_action();
+        let index = match self.todo_items.current_selection {
+            Some(index) => index,
+            None => return,
+        };
+        let current = self
+            .app_state
+            .todo_items
+            .borrow()
+            .get(index)
+            .map(|todo| todo.due);
+        if current != Some(due) {
+            self.update_todo(index, todo_due, |_| due);
+        }
+    }
+
+    /// Asks for the branch of the selected todo, offering a name made from
+    /// its title if it has none yet.
+    pub fn on_branch(&mut self) {
+        let index = match self.todo_items.current_selection {
+            Some(index) => index,
+            None => return,
+        };
+        let branch = match self.app_state.todo_items.borrow().get(index) {
+            Some(todo) => todo
+                .branch
+                .clone()
+                .unwrap_or_else(|| todo.suggested_branch()),
+            None => return,
+        };
+        self.on_stop_action();
+        self.tabs.index = 0;
+        self.current_action = CodePalAction::Branch;
+        self.branch_editor.text = branch;
+        self.branch_editor.on_activate();
+    }
+
+    /// Sets the branch of the selected todo and checks it out in the
+    /// background, in the directory of its project. An empty name unlinks
+    /// the branch, an invalid one keeps asking.
+    pub fn set_branch(&mut self, name: &str) {
+        let branch = Some(name.trim().to_string()).filter(|name| !name.is_empty());
+        if let Some(name) = branch.as_ref().filter(|name| !git::valid_branch_name(name)) {
+            self.message = Some(format!("{:?} isn't a valid branch name", name));
+            return;
+        }
+        self.on_stop_action();
+        let index = match self.todo_items.current_selection {
+            Some(index) => index,
+            None => return,
+        };
+        let current = self
+            .app_state
+            .todo_items
+            .borrow()
+            .get(index)
+            .map(|todo| todo.branch.clone());
+        if current != Some(branch.clone()) {
+            let value = branch.clone();
+            self.update_todo(index, todo_branch, |_| value);
+        }
+        let (dir, branch) = match (branch, self.branch_source()) {
+            (None, _) => return,
+            (Some(_), None) => {
+                self.message = Some(String::from(
+                    "The todo's project has no directory to check the branch out in",
+                ));
+                return;
+            }
+            (Some(_), Some(source)) => source,
+        };
+        self.message = Some(format!("Checking out {}…", branch));
+        let sender = Sender::clone(&self.sender);
+        thread::spawn(move || {
+            let created = git::checkout(&git::expand_home(&dir), &branch);
+            let created = created.map_err(|e| e.to_string());
+            sender
+                .send(Event::Action(ActionPayload::CheckedOut(branch, created)))
+                .unwrap_or_default();
+        });
+    }
+
+    pub fn on_search(&mut self) {
+        self.on_stop_action();
+        self.current_action = CodePalAction::Search;
+        let query = self.search.query.clone();
+        self.set_search_results(&query);
+        self.search.on_activate();
+    }
+
+    pub fn set_search_results(&mut self, query: &str) {
+        let results = search(
+            query,
+            &self.app_state.todo_items.borrow(),
+            &self.app_state.projects.borrow(),
+        );
+        self.search.set_results(results);
+    }
+
+    /// Closes the search and selects the todo, note or project it found.
+    pub fn on_search_result(&mut self, index: usize) {
+        let target = match self.search.results.get(index) {
+            Some(result) => result.target,
+            None => return,
+        };
+        self.on_stop_action();
+        match target {
+            SearchTarget::Todo(i) => {
+                self.tabs.index = 0;
+                self.select_todo(i);
+                self.on_add_todo();
+            }
+            SearchTarget::Note(i, j) => {
+                self.tabs.index = 0;
+                self.select_todo(i);
+                self.select_note(j);
+                self.on_add_note();
+            }
+            SearchTarget::Project(i, column) => {
+                self.tabs.index = 1;
+                self.on_add_project();
+                self.projects.select_item(i);
+                self.projects.select_header(column);
+            }
+        }
+    }
+
+    /// Shows the keys as they are bound.
+    pub fn on_help(&mut self) {
+        self.on_stop_action();
+        self.help
+            .show(help::sections(&self.keymap, self.modal.is_some()));
+        self.current_action = CodePalAction::Help;
+    }
+
+    pub fn set_layout(&mut self, layout: LayoutConfig) {
+        self.projects.column_widths = layout.project_columns.clone();
+        self.layout = layout;
+    }
+
+    /// Moves a border of the notes tab and keeps the layout in the config.
+    pub fn on_resize(&mut self, action: Action) {
+        let step = RESIZE_STEP as i16;
+        let layout = &mut self.layout;
+        match action {
+            Action::WidenList => layout.list_width = layout::resize(layout.list_width, step),
+            Action::NarrowList => layout.list_width = layout::resize(layout.list_width, -step),
+            Action::GrowTodos => layout.todo_height = layout::resize(layout.todo_height, step),
+            Action::ShrinkTodos => layout.todo_height = layout::resize(layout.todo_height, -step),
+            Action::ResetLayout => layout.reset(),
+            _ => return,
+        }
+        if let Some(path) = &self.config_path {
+            if let Err(e) = Config::save_layout(path, &self.layout) {
+                self.message = Some(format!("Couldn't save the layout: {}", e));
+            }
+        }
+    }
+
+    pub fn on_cycle_sort(&mut self) {
+        self.todo_view.sort = self.todo_view.sort.next();
+        self.message = Some(format!("Sorted by {}", self.todo_view.sort.name()));
+    }
+
+    pub fn on_toggle_grouping(&mut self) {
+        self.todo_view.group_by_project = !self.todo_view.group_by_project;
+    }
+
+    pub fn on_quit(&mut self) {
+        if !self.dirty {
+            self.should_quit = true;
+            return;
+        }
+        self.on_stop_action();
+        self.current_action = CodePalAction::ConfirmQuit;
+        self.message = Some(String::from(
+            "Unsaved changes: (s)ave and quit, (d)iscard and quit, (esc) cancel",
+        ));
+    }
+
+    pub fn on_confirm_quit(&mut self, event: KeyEvent) {
+        match event.code {
+            KeyCode::Char('s') | KeyCode::Char('y') => {
+                self.on_save();
+                self.should_quit = !self.dirty;
+            }
+            KeyCode::Char('d') | KeyCode::Char('n') => self.should_quit = true,
+            KeyCode::Esc => {
+                self.current_action = CodePalAction::None;
+                self.message = None;
+            }
+            _ => {}
+        }
+    }
+
     pub fn on_stop_action(&mut self) {
         if let Some(x) = self.current_active_item() {
             x.on_deactivate();
@@ -186,50 +597,765 @@ impl<'a> App<'a> {
         }
     }
 
+    /// The title of the part of the ui that has the focus.
+    pub fn focus(&self) -> Option<&str> {
+        Some(match self.current_action {
+            CodePalAction::AddToDoItem => &self.todo_items.title,
+            CodePalAction::AddNote => &self.notes.title,
+            CodePalAction::EditNote => &self.note_pane.title,
+            CodePalAction::EditDescription => &self.description_pane.title,
+            CodePalAction::AddProject => &self.projects.title,
+            CodePalAction::SelectProject => &self.todo_item_project.title,
+            CodePalAction::SelectWorkspace => &self.workspace_switcher.title,
+            CodePalAction::Filter => &self.todo_filter.title,
+            CodePalAction::Due => &self.due_editor.title,
+            CodePalAction::Branch => &self.branch_editor.title,
+            CodePalAction::Search => &self.search.title,
+            _ => return None,
+        })
+    }
+
     pub fn current_active_item(&mut self) -> Option<&mut dyn UIEventProcessor> {
-        match self.current_action {
+        self.item(self.current_action)
+    }
+
+    /// The part of the ui that `action` is done in.
+    fn item(&mut self, action: CodePalAction) -> Option<&mut dyn UIEventProcessor> {
+        match action {
             CodePalAction::AddToDoItem => Some(&mut self.todo_items),
             CodePalAction::AddNote => Some(&mut self.notes),
+            CodePalAction::EditNote => Some(&mut self.note_pane),
+            CodePalAction::EditDescription => Some(&mut self.description_pane),
             CodePalAction::AddProject => Some(&mut self.projects),
             CodePalAction::SelectProject => Some(&mut self.todo_item_project),
+            CodePalAction::SelectWorkspace => Some(&mut self.workspace_switcher),
+            CodePalAction::Filter => Some(&mut self.todo_filter),
+            CodePalAction::Due => Some(&mut self.due_editor),
+            CodePalAction::Branch => Some(&mut self.branch_editor),
+            CodePalAction::Search => Some(&mut self.search),
             _ => None,
         }
     }
 
     pub fn on_key(&mut self, event: KeyEvent) {
-        match (event.code, event.modifiers) {
-            (KeyCode::Char('q'), KeyModifiers::CONTROL) => {
-                self.should_quit = true;
+        if let CodePalAction::ConfirmQuit = self.current_action {
+            self.on_confirm_quit(event);
+            return;
+        }
+        if let CodePalAction::Help = self.current_action {
+            match (self.keymap.action(None, event), event.code) {
+                (Some(Action::Help), _) | (Some(Action::Cancel), _) | (_, KeyCode::Char('q')) => {
+                    self.current_action = CodePalAction::None
+                }
+                _ => self.help.on_event(event),
+            }
+            return;
+        }
+        if !self.on_modal_key(event) {
+            // Plain characters type while something is edited, whatever
+            // they are bound to.
+            let typing = self.current_active_item().is_some() && self.mode() != Some(Mode::Normal);
+            let character = matches!(event.code, KeyCode::Char(_))
+                && !event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+            let action = match typing && character {
+                true => None,
+                false => self.keymap.action(self.key_context(), event),
+            };
+            match action {
+                Some(action) => self.run_action(action, event),
+                None => {
+                    let typing = self.mode() != Some(Mode::Normal);
+                    if let Some(x) = self.current_active_item().filter(|_| typing) {
+                        x.on_event(event);
+                        self.record_changes();
+                    }
+                }
+            }
+        }
+        self.refresh_todo_view();
+        self.refresh_text_panes();
+        self.refresh_repo_statuses();
+        self.refresh_branch_commits();
+    }
+
+    /// Clicking a tab switches to it, clicking a pane starts what its key
+    /// would and hands it the click, the wheel scrolls the pane below it.
+    pub fn on_mouse(&mut self, event: MouseEvent) {
+        let (column, row) = match event {
+            MouseEvent::Down(MouseButton::Left, column, row, _)
+            | MouseEvent::ScrollUp(column, row, _)
+            | MouseEvent::ScrollDown(column, row, _) => (column, row),
+            _ => return,
+        };
+        let clicked = matches!(event, MouseEvent::Down(..));
+        match self.current_action {
+            CodePalAction::Help => {
+                let code = match event {
+                    MouseEvent::ScrollUp(..) => KeyCode::Up,
+                    MouseEvent::ScrollDown(..) => KeyCode::Down,
+                    _ => return,
+                };
+                self.help
+                    .on_event(KeyEvent::new(code, KeyModifiers::empty()));
+                return;
             }
-            (KeyCode::Char('a'), KeyModifiers::CONTROL) => self.on_add_todo(),
-            (KeyCode::Char('n'), KeyModifiers::CONTROL) => self.on_add_note(),
-            (KeyCode::Char('p'), KeyModifiers::CONTROL) => match self.tabs.index {
+            CodePalAction::ConfirmQuit | CodePalAction::Search => return,
+            _ => {}
+        }
+        if clicked && contains(self.tabs.area, column, row) {
+            if let Some(index) = self.tabs.title_at(column) {
+                self.on_stop_action();
+                self.tabs.index = index;
+                self.refresh_repo_statuses();
+                self.refresh_branch_commits();
+            }
+            return;
+        }
+        let pane = match self.pane_at(column, row) {
+            Some(pane) => pane,
+            None => return,
+        };
+        if clicked && pane != self.current_action {
+            self.start(pane);
+        }
+        if let Some(x) = self.item(pane) {
+            x.on_mouse(event);
+        }
+        self.refresh_todo_view();
+        self.refresh_text_panes();
+        self.refresh_repo_statuses();
+        self.refresh_branch_commits();
+    }
+
+    /// What is done in the pane at `column`, `row` of the current tab.
+    fn pane_at(&self, column: u16, row: u16) -> Option<CodePalAction> {
+        let panes = match self.tabs.index {
+            0 => vec![
+                (CodePalAction::AddToDoItem, self.todo_items.area),
+                (CodePalAction::SelectProject, self.todo_item_project.area),
+                (CodePalAction::AddNote, self.notes.area),
+                (CodePalAction::EditDescription, self.description_pane.area),
+                (CodePalAction::EditNote, self.note_pane.area),
+                (CodePalAction::Filter, self.todo_filter.area),
+                (CodePalAction::Branch, self.branch_editor.area),
+            ],
+            1 => vec![(CodePalAction::AddProject, self.projects.area)],
+            2 => vec![(CodePalAction::SelectWorkspace, self.workspace_switcher.area)],
+            _ => vec![],
+        };
+        panes
+            .into_iter()
+            .find(|(_, area)| contains(*area, column, row))
+            .map(|(action, _)| action)
+    }
+
+    /// Starts `action` the way its key does.
+    fn start(&mut self, action: CodePalAction) {
+        match action {
+            CodePalAction::AddToDoItem => self.on_add_todo(),
+            CodePalAction::AddNote => self.on_add_note(),
+            CodePalAction::EditNote => self.on_edit_note(),
+            CodePalAction::EditDescription => self.on_edit_description(),
+            CodePalAction::AddProject => self.on_add_project(),
+            CodePalAction::SelectProject => self.on_select_project(),
+            CodePalAction::SelectWorkspace => self.on_select_workspace(),
+            CodePalAction::Filter => self.on_filter(),
+            CodePalAction::Branch => self.on_branch(),
+            _ => {}
+        }
+    }
+
+    /// Does what a key is bound to, `event` being the key.
+    fn run_action(&mut self, action: Action, event: KeyEvent) {
+        match action {
+            Action::Quit => self.on_quit(),
+            Action::AddTodo => self.on_add_todo(),
+            Action::AddNote => self.on_add_note(),
+            Action::EditNote => self.on_edit_note(),
+            Action::EditDescription => self.on_edit_description(),
+            Action::Project => match self.tabs.index {
                 0 => self.on_select_project(),
                 1 => self.on_add_project(),
                 _ => {}
             },
-            (KeyCode::Char('s'), KeyModifiers::CONTROL) => self.on_save(),
-            (KeyCode::Esc, _) => self.on_stop_action(),
-            _ => {
+            Action::Save => self.on_save(),
+            Action::SelectWorkspace => self.on_select_workspace(),
+            Action::Filter => self.on_filter(),
+            Action::Search => self.on_search(),
+            Action::CycleSort => self.on_cycle_sort(),
+            Action::ToggleGrouping => self.on_toggle_grouping(),
+            Action::Undo => self.on_undo(),
+            Action::Redo => self.on_redo(),
+            Action::CycleStatus => {
+                self.update_selected_todo(todo_status, |todo| todo.status.next())
+            }
+            Action::CyclePriority => {
+                self.update_selected_todo(todo_priority, |todo| todo.priority.next())
+            }
+            Action::OpenInEditor => self.on_open_in_editor(),
+            Action::NextDue => self
+                .update_selected_todo(todo_due, |todo| todo.next_due(Local::today().naive_local())),
+            Action::SetDue => self.on_set_due(),
+            Action::Branch => self.on_branch(),
+            Action::Cancel => self.on_stop_action(),
+            Action::Help => self.on_help(),
+            Action::WidenList
+            | Action::NarrowList
+            | Action::GrowTodos
+            | Action::ShrinkTodos
+            | Action::ResetLayout => self.on_resize(action),
+            // The normal mode of modal editing doesn't type.
+            action if action.edits_text() && self.mode() == Some(Mode::Normal) => {}
+            action => {
                 if let Some(x) = self.current_active_item() {
-                    x.on_event(event);
+                    match action {
+                        Action::NextTab | Action::PreviousTab => x.on_event(event),
+                        _ => x.on_action(action),
+                    }
+                    self.record_changes();
                 } else {
-                    match (event.code, event.modifiers) {
-                        (KeyCode::Left, _) => self.on_left(),
-                        (KeyCode::Up, _) => self.on_up(),
-                        (KeyCode::Right, _) => self.on_right(),
-                        (KeyCode::Down, _) => self.on_down(),
-                        (KeyCode::Enter, _) => self.on_enter(),
-                        (_, _) => {}
+                    match action {
+                        Action::PreviousTab => self.on_left(),
+                        Action::NextTab => self.on_right(),
+                        _ => {}
                     }
                 }
             }
         }
     }
 
-    pub fn on_enter(&mut self) {}
+    /// The mode of modal editing, while it's on and a list is edited.
+    pub fn mode(&self) -> Option<Mode> {
+        match self.current_action {
+            CodePalAction::AddToDoItem | CodePalAction::AddNote => {
+                self.modal.as_ref().map(|modal| modal.mode)
+            }
+            _ => None,
+        }
+    }
+
+    fn set_mode(&mut self, mode: Mode) {
+        if let Some(modal) = &mut self.modal {
+            modal.set_mode(mode);
+        }
+    }
+
+    /// Handles the keys that modal editing gives a meaning of its own,
+    /// returns whether `event` was one of them.
+    fn on_modal_key(&mut self, event: KeyEvent) -> bool {
+        let modal = match (self.mode(), &mut self.modal) {
+            (Some(_), Some(modal)) => modal,
+            _ => return false,
+        };
+        let plain = !event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let key = match (modal.mode, event.code) {
+            (Mode::Insert, KeyCode::Esc) => {
+                modal.set_mode(Mode::Normal);
+                return true;
+            }
+            (Mode::Normal, KeyCode::Char(c)) if event.modifiers == KeyModifiers::CONTROL => {
+                modal.on_normal_ctrl_key(c)
+            }
+            (Mode::Normal, KeyCode::Char(c)) if plain => modal.on_normal_key(c),
+            _ => return false,
+        };
+        match key {
+            NormalKey::Action(action) => self.run_action(action, event),
+            NormalKey::Key(key) => {
+                if let Some(x) = self.current_active_item() {
+                    x.on_event(key);
+                    self.record_changes();
+                }
+            }
+            NormalKey::Insert(code) => {
+                if let (Some(code), Some(x)) = (code, self.current_active_item()) {
+                    x.on_event(KeyEvent::new(code, KeyModifiers::empty()));
+                }
+                self.set_mode(Mode::Insert);
+            }
+            NormalKey::Open(action) => {
+                self.run_action(action, event);
+                self.set_mode(Mode::Insert);
+            }
+            NormalKey::Pending => {}
+            NormalKey::Ignored => return false,
+        }
+        true
+    }
+
+    /// The bindings that apply besides the global ones.
+    fn key_context(&self) -> Option<Context> {
+        match self.current_action {
+            CodePalAction::AddToDoItem | CodePalAction::None => Some(Context::TodoList),
+            CodePalAction::AddNote => Some(Context::Notes),
+            CodePalAction::AddProject => Some(Context::Projects),
+            CodePalAction::SelectProject | CodePalAction::SelectWorkspace => {
+                Some(Context::AutoComplete)
+            }
+            _ => None,
+        }
+    }
+
+    /// Sets a field of the selected todo to the value computed from it.
+    fn update_selected_todo<V: 'static>(
+        &mut self,
+        accessor: fn(&mut TodoItem) -> &mut V,
+        value: impl FnOnce(&TodoItem) -> V,
+    ) {
+        if let Some(index) = self.todo_items.current_selection {
+            self.update_todo(index, accessor, value);
+        }
+    }
+
+    /// Sets a field of the todo at `index` to the value computed from it.
+    fn update_todo<V: 'static>(
+        &mut self,
+        index: usize,
+        accessor: fn(&mut TodoItem) -> &mut V,
+        value: impl FnOnce(&TodoItem) -> V,
+    ) {
+        let list = self.app_state.todo_items.clone();
+        let value = match list.borrow().get(index) {
+            Some(todo) => value(todo),
+            None => return,
+        };
+        let mut command = Box::new(SetField {
+            list,
+            index,
+            accessor,
+            value,
+        });
+        command.apply();
+        self.touch(&Focus::TodoItems(index));
+        self.history.record(command, Focus::TodoItems(index));
+        self.mark_dirty();
+    }
+
+    /// Asks for the selected note, when in the notes, or otherwise the
+    /// description of the selected todo to be edited in `$EDITOR`.
+    pub fn on_open_in_editor(&mut self) {
+        let index = match self.todo_items.current_selection {
+            Some(index) => index,
+            None => return,
+        };
+        let todos = self.app_state.todo_items.borrow();
+        let todo = match todos.get(index) {
+            Some(todo) => todo,
+            None => return,
+        };
+        let in_notes = matches!(
+            self.current_action,
+            CodePalAction::AddNote | CodePalAction::EditNote
+        );
+        let edit = match self.notes.current_selection {
+            Some(note) if in_notes => todo.notes.borrow().get(note).map(|n| ExternalEdit {
+                target: ExternalTarget::Note(todo.identifier.clone(), note),
+                content: n.content.clone(),
+            }),
+            _ => Some(ExternalEdit {
+                target: ExternalTarget::Description(todo.identifier.clone()),
+                content: todo.description.join("\n"),
+            }),
+        };
+        drop(todos);
+        self.external_edit = edit;
+    }
+
+    /// Writes back what came out of `$EDITOR`, as one step that can be undone.
+    pub fn on_external_edit(&mut self, edit: ExternalEdit, result: Result<String, Error>) {
+        let content = match result {
+            Ok(content) if content == edit.content => {
+                self.message = Some(String::from("No changes"));
+                return;
+            }
+            Ok(content) => content,
+            Err(e) => {
+                self.message = Some(format!("Editor failed: {}", e));
+                return;
+            }
+        };
+        let identifier = match &edit.target {
+            ExternalTarget::Note(identifier, _) | ExternalTarget::Description(identifier) => {
+                identifier
+            }
+        };
+        let position = self
+            .app_state
+            .todo_items
+            .borrow()
+            .iter()
+            .position(|todo| &todo.identifier == identifier);
+        let index = match position {
+            Some(index) => index,
+            None => {
+                self.message = Some(String::from("The todo is gone, the edit was dropped"));
+                return;
+            }
+        };
+        match edit.target {
+            ExternalTarget::Note(identifier, note) => {
+                let notes = self.app_state.todo_items.borrow()[index].notes.clone();
+                let removed = match notes.borrow().get(note) {
+                    Some(n) => n.content.clone(),
+                    None => {
+                        self.message = Some(String::from("The note is gone, the edit was dropped"));
+                        return;
+                    }
+                };
+                let mut command = Box::new(TextEdit {
+                    list: notes,
+                    index: note,
+                    field: 0,
+                    accessor: note_content,
+                    position: 0,
+                    removed,
+                    inserted: content,
+                    kind: EditKind::Other,
+                });
+                command.apply();
+                let focus = Focus::Notes(identifier, note);
+                self.touch(&focus);
+                self.history.record(command, focus);
+                self.mark_dirty();
+            }
+            ExternalTarget::Description(_) => {
+                let lines = content.lines().map(String::from).collect();
+                self.update_todo(index, todo_description, |_| lines);
+            }
+        }
+    }
+
+    /// Updates the modification time of the todo an edit happened in.
+    fn touch(&mut self, focus: &Focus) {
+        let mut todos = self.app_state.todo_items.borrow_mut();
+        let todo = match focus {
+            Focus::TodoItems(index) => todos.get_mut(*index),
+            Focus::Notes(identifier, _) => {
+                todos.iter_mut().find(|todo| &todo.identifier == identifier)
+            }
+            Focus::Projects(_) => None,
+        };
+        if let Some(todo) = todo {
+            todo.updated_at = Utc::now();
+        }
+    }
+
+    /// Moves the commands applied by the active editor into the history.
+    pub fn record_changes(&mut self) {
+        let changes = match self.current_active_item() {
+            Some(x) => x.take_changes(),
+            None => return,
+        };
+        for command in changes {
+            let index = command.index();
+            let focus = match self.current_action {
+                CodePalAction::AddNote | CodePalAction::EditNote => {
+                    Focus::Notes(self.selected_todo_identifier(), index)
+                }
+                CodePalAction::AddProject => Focus::Projects(index),
+                _ => Focus::TodoItems(index),
+            };
+            match focus {
+                Focus::TodoItems(_) if !command.in_place() => {}
+                _ => self.touch(&focus),
+            }
+            self.history.record(command, focus);
+            self.mark_dirty();
+        }
+    }
+
+    pub fn on_undo(&mut self) {
+        match self.history.undo() {
+            Some(focus) => self.on_history_change(focus),
+            None => self.message = Some(String::from("Nothing to undo")),
+        }
+    }
+
+    pub fn on_redo(&mut self) {
+        match self.history.redo() {
+            Some(focus) => self.on_history_change(focus),
+            None => self.message = Some(String::from("Nothing to redo")),
+        }
+    }
+
+    /// Selects the row an undone or redone command touched.
+    fn on_history_change(&mut self, focus: Focus) {
+        self.mark_dirty();
+        match focus {
+            Focus::TodoItems(index) => self.select_todo(index),
+            Focus::Notes(identifier, index) => {
+                let position = self
+                    .app_state
+                    .todo_items
+                    .borrow()
+                    .iter()
+                    .position(|todo| todo.identifier == identifier);
+                if let Some(position) = position {
+                    self.select_todo(position);
+                    self.select_note(index);
+                }
+            }
+            Focus::Projects(index) => {
+                self.tabs.index = 1;
+                let len = self.app_state.projects.borrow().len();
+                self.projects.current_selection = len.checked_sub(1).map(|last| index.min(last));
+            }
+        }
+    }
 
-    pub fn on_tick(&mut self) {}
+    /// Selects the todo at `index` right away rather than through the event
+    /// loop, so that a note selected right after it isn't reset.
+    fn select_todo(&mut self, index: usize) {
+        let len = self.app_state.todo_items.borrow().len();
+        self.todo_items.current_selection = len.checked_sub(1).map(|last| index.min(last));
+        self.set_notes();
+        self.set_todo_item_project_suggestions();
+    }
+
+    fn select_note(&mut self, index: usize) {
+        let len = self
+            .notes
+            .current_text
+            .as_ref()
+            .map_or(0, |notes| notes.borrow().len());
+        self.notes.current_selection = len.checked_sub(1).map(|last| index.min(last));
+    }
+
+    fn selected_todo_identifier(&self) -> String {
+        self.todo_items
+            .current_selection
+            .and_then(|index| {
+                self.app_state
+                    .todo_items
+                    .borrow()
+                    .get(index)
+                    .map(|todo| todo.identifier.clone())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Autosaves and expires messages, returns whether that changed
+    /// anything on screen.
+    pub fn on_tick(&mut self) -> bool {
+        let shown = (self.message.clone(), self.dirty);
+        self.expire_message();
+        if let (true, Some(delay), Some(last_edit)) =
+            (self.dirty, self.autosave_delay, self.last_edit)
+        {
+            if last_edit.elapsed() >= delay {
+                self.on_save();
+            }
+        }
+        self.refresh_repo_statuses();
+        self.refresh_branch_commits();
+        shown != (self.message.clone(), self.dirty)
+    }
+
+    /// Looks at the repositories of the projects in the background, every
+    /// so often while the projects tab is shown.
+    pub fn refresh_repo_statuses(&mut self) {
+        let stale = self
+            .repos_checked
+            .is_none_or(|checked| checked.elapsed() >= REPO_REFRESH);
+        if self.tabs.index != 1 || self.checking_repos || !stale {
+            return;
+        }
+        let dirs: Vec<_> = self
+            .app_state
+            .projects
+            .borrow()
+            .iter()
+            .filter(|project| !project.dir_location.trim().is_empty())
+            .map(|project| (project.identifier.clone(), project.dir_location.clone()))
+            .collect();
+        self.checking_repos = true;
+        self.repos_checked = Some(Instant::now());
+        let sender = Sender::clone(&self.sender);
+        thread::spawn(move || {
+            let statuses = dirs
+                .into_iter()
+                .map(|(identifier, dir)| {
+                    let status = git::status(&git::expand_home(dir.trim()));
+                    (identifier, status.map_err(|e| e.to_string()))
+                })
+                .collect();
+            sender
+                .send(Event::Action(ActionPayload::RepoStatuses(statuses)))
+                .unwrap_or_default();
+        });
+    }
+
+    /// The directory of the project of the selected todo, if it has one.
+    pub fn project_dir(&self) -> Option<String> {
+        let index = self.todo_items.current_selection?;
+        let todos = self.app_state.todo_items.borrow();
+        let todo = todos.get(index)?;
+        let projects = self.app_state.projects.borrow();
+        let project = projects
+            .iter()
+            .find(|project| project.identifier == todo.project_identifier)?;
+        let dir = project.dir_location.trim();
+        match dir.is_empty() {
+            true => None,
+            false => Some(dir.to_string()),
+        }
+    }
+
+    /// The directory of the project of the selected todo and the todo's
+    /// branch, if it has both.
+    pub fn branch_source(&self) -> Option<(String, String)> {
+        let index = self.todo_items.current_selection?;
+        let branch = self
+            .app_state
+            .todo_items
+            .borrow()
+            .get(index)?
+            .branch
+            .clone()?;
+        Some((self.project_dir()?, branch))
+    }
+
+    /// Looks at the commits on the branch of the selected todo in the
+    /// background, when another branch is selected and every so often.
+    pub fn refresh_branch_commits(&mut self) {
+        let source = match self.branch_source() {
+            Some(source) if self.tabs.index == 0 => source,
+            _ => return,
+        };
+        let stale = match &self.branch_checked {
+            Some((checked, at)) => *checked != source || at.elapsed() >= REPO_REFRESH,
+            None => true,
+        };
+        if self.checking_branch || !stale {
+            return;
+        }
+        self.checking_branch = true;
+        self.branch_checked = Some((source.clone(), Instant::now()));
+        let sender = Sender::clone(&self.sender);
+        thread::spawn(move || {
+            let (dir, branch) = &source;
+            let commits = git::branch_commits(&git::expand_home(dir), branch);
+            let commits = commits.map_err(|e| e.to_string());
+            sender
+                .send(Event::Action(ActionPayload::BranchCommits(source, commits)))
+                .unwrap_or_default();
+        });
+    }
+
+    /// Whether the tab shown has repositories that ticks look at again.
+    fn polling_repos(&self) -> bool {
+        match self.tabs.index {
+            0 => self.branch_source().is_some(),
+            1 => self
+                .app_state
+                .projects
+                .borrow()
+                .iter()
+                .any(|project| !project.dir_location.trim().is_empty()),
+            _ => false,
+        }
+    }
+
+    /// Whether ticks have nothing to do: there is nothing to autosave, no
+    /// message to expire and no repository to look at again.
+    pub fn is_idle(&self) -> bool {
+        let autosaving = self.dirty && self.autosave_delay.is_some() && self.last_edit.is_some();
+        !autosaving && self.message.is_none() && !self.polling_repos()
+    }
+
+    /// Clears a message once it has been shown for a while, unless it's a
+    /// question.
+    fn expire_message(&mut self) {
+        let message = match &self.message {
+            Some(message) => message,
+            None => {
+                self.message_shown = None;
+                return;
+            }
+        };
+        match &self.message_shown {
+            Some((shown, since)) if shown == message => {
+                let asking = matches!(self.current_action, CodePalAction::ConfirmQuit);
+                if since.elapsed() >= MESSAGE_TIMEOUT && !asking {
+                    self.message = None;
+                    self.message_shown = None;
+                }
+            }
+            _ => self.message_shown = Some((message.clone(), Instant::now())),
+        }
+    }
+
+    /// Recomputes the rows shown in the todo list. The selected item stays
+    /// visible even if it no longer matches the filter, until it's left.
+    pub fn refresh_todo_view(&mut self) {
+        self.todo_items.view = if self.todo_view.is_default() {
+            None
+        } else {
+            Some(self.todo_view.rows(
+                &self.app_state.todo_items.borrow(),
+                &self.app_state.projects.borrow(),
+                self.todo_items.current_selection,
+            ))
+        };
+    }
+
+    pub fn set_filter(&mut self, query: &str) {
+        self.todo_view.set_query(query);
+        let rows = self.todo_view.rows(
+            &self.app_state.todo_items.borrow(),
+            &self.app_state.projects.borrow(),
+            None,
+        );
+        let first = rows.iter().find_map(|row| match row {
+            ViewRow::Item(i) => Some(*i),
+            ViewRow::Group(_) => None,
+        });
+        let selection = self.todo_items.current_selection;
+        if let Some(first) = first {
+            if !selection.is_some_and(|s| rows.contains(&ViewRow::Item(s))) {
+                self.todo_items.select_item(first);
+            }
+        }
+        self.refresh_todo_view();
+    }
+
+    /// Shows the selected note and the description and branch of the
+    /// selected todo in their panes.
+    pub fn refresh_text_panes(&mut self) {
+        let note = match (&self.notes.current_text, self.notes.current_selection) {
+            (Some(notes), Some(index)) => Some(PaneText::Note(notes.clone(), index)),
+            _ => None,
+        };
+        self.note_pane.show(note);
+        let description = self
+            .todo_items
+            .current_selection
+            .map(|index| PaneText::Description(self.app_state.todo_items.clone(), index));
+        self.description_pane.show(description);
+        if self.current_action != CodePalAction::Branch {
+            self.branch_editor.text = self
+                .todo_items
+                .current_selection
+                .and_then(|index| {
+                    self.app_state
+                        .todo_items
+                        .borrow()
+                        .get(index)?
+                        .branch
+                        .clone()
+                })
+                .unwrap_or_default();
+        }
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+        self.last_edit = Some(Instant::now());
+    }
 
     pub fn set_notes(&mut self) {
         let item_ref = self.app_state.todo_items.clone();
@@ -263,33 +1389,22 @@ impl<'a> App<'a> {
                 if items.len() > 0 {
                     let project_name = &items.get(0).unwrap().name;
                     self.todo_item_project.text = project_name.clone();
-                    self.set_project_suggestions(&project_name);
                 } else {
                     self.todo_item_project.text = String::from("");
-                    self.set_project_suggestions("");
                 }
+                self.set_project_suggestions();
             }
             None => {
                 self.todo_item_project.text = String::from("");
-                self.set_project_suggestions("");
+                self.set_project_suggestions();
             }
         };
     }
 
-    pub fn set_project_suggestions(&mut self, text: &str) {
-        let item_ref = self.app_state.projects.clone();
-        let borrowed = item_ref.borrow();
-        let mut cloned_list: Vec<_> = borrowed.iter().map(|x| x.clone()).collect();
-        let mut i = 0;
-        while i != cloned_list.len() {
-            if !cloned_list[i].get_name().contains(text) {
-                cloned_list.remove(i);
-            } else {
-                i += 1;
-            }
-        }
-        self.todo_item_project.current_suggestions = cloned_list;
-        self.todo_item_project.select_suggestion(Some(0));
+    /// Offers the current projects, the editor ranks them against its text.
+    pub fn set_project_suggestions(&mut self) {
+        let projects = self.app_state.projects.borrow().clone();
+        self.todo_item_project.set_items(projects);
     }
 
     pub fn set_project(&mut self, identifier: &str) {
@@ -297,9 +1412,9 @@ impl<'a> App<'a> {
         let selected_index = self.todo_items.current_selection;
         match selected_index {
             Some(index) => {
-                let mut borrowed = item_ref.borrow_mut();
-                let todo = borrowed.get_mut(index).unwrap();
-                todo.project_identifier = identifier.to_string();
+                if item_ref.borrow()[index].project_identifier != identifier {
+                    self.update_selected_todo(todo_project, |_| identifier.to_string());
+                }
             }
             None => {
                 self.notes.current_text = None;
@@ -307,26 +1422,130 @@ impl<'a> App<'a> {
         };
     }
 
+    /// Offers the existing workspaces and, when the typed name is new, one
+    /// to create.
+    pub fn set_workspace_suggestions(&mut self) {
+        let workspaces = self
+            .workspaces
+            .names()
+            .into_iter()
+            .map(|name| Workspace { name, exists: true })
+            .collect();
+        self.workspace_switcher.set_items(workspaces);
+        let text = self.workspace_switcher.text.clone();
+        if !text.is_empty() && !self.workspace_switcher.items.iter().any(|w| w.name == text) {
+            self.workspace_switcher.add_suggestion(Workspace {
+                name: text,
+                exists: false,
+            });
+        }
+    }
+
+    pub fn switch_workspace(&mut self, name: &str) {
+        if name == self.workspace {
+            self.on_stop_action();
+            return;
+        }
+        let mut store = match self.workspaces.open(name) {
+            Ok(store) => store,
+            Err(e) => {
+                self.message = Some(e.to_string());
+                return;
+            }
+        };
+        if let Err(e) = self.store.save(&self.app_state) {
+            self.message = Some(format!("Save failed: {}", e));
+            return;
+        }
+        match store.load() {
+            Ok(app_state) => {
+                self.app_state = app_state;
+                self.store = store;
+                self.workspace = name.to_string();
+                self.dirty = false;
+                self.last_edit = None;
+                self.history.clear();
+                self.todo_items.current_selection = None;
+                self.projects.current_selection = None;
+                self.init_state();
+                self.on_stop_action();
+                self.tabs.index = 0;
+                self.message = Some(format!("Switched to workspace {}", name));
+            }
+            Err(e) => {
+                self.message = Some(format!("Couldn't open workspace {}: {}", name, e));
+            }
+        }
+    }
+
     pub fn on_action(&mut self, action: ActionPayload) {
         match action {
-            ActionPayload::Selection(sender, _) => {
+            ActionPayload::Selection(sender, selection) => {
                 if sender == "Todo Items" {
                     self.set_notes();
                     self.set_todo_item_project_suggestions();
+                } else if sender == "Search" {
+                    if let Some(index) = selection {
+                        self.on_search_result(index);
+                    }
                 }
             }
-            ActionPayload::Text(text) => {
-                self.set_project_suggestions(&text);
-            }
+            ActionPayload::Text(text) => match self.current_action {
+                CodePalAction::SelectWorkspace => self.set_workspace_suggestions(),
+                CodePalAction::Filter => self.set_filter(&text),
+                CodePalAction::Due | CodePalAction::Branch => {}
+                CodePalAction::Search => self.set_search_results(&text),
+                _ => self.set_project_suggestions(),
+            },
             ActionPayload::TextSelection(sender, identifier) => {
                 if sender == "Project" {
                     self.set_project(&identifier);
+                } else if sender == "Workspace" {
+                    self.switch_workspace(&identifier);
+                } else if sender == "Filter" {
+                    self.on_stop_action();
+                } else if sender == "Due date" {
+                    self.set_due(&identifier);
+                } else if sender == "Branch" {
+                    self.set_branch(&identifier);
                 }
             }
+            ActionPayload::RepoStatuses(statuses) => {
+                self.checking_repos = false;
+                self.repo_statuses = statuses.into_iter().collect();
+            }
+            ActionPayload::BranchCommits(source, commits) => {
+                self.checking_branch = false;
+                self.branch_commits.insert(source, commits);
+                // Another branch may have been selected in the meantime.
+                self.refresh_branch_commits();
+            }
+            ActionPayload::CheckedOut(branch, created) => {
+                self.message = Some(match created {
+                    Ok(true) => format!("Created and checked out {}", branch),
+                    Ok(false) => format!("Checked out {}", branch),
+                    Err(e) => format!("Couldn't check out {}: {}", branch, e),
+                });
+                self.repos_checked = None;
+                self.branch_checked = None;
+                self.refresh_branch_commits();
+            }
         }
+        self.refresh_text_panes();
     }
 
     pub fn on_save(&mut self) {
-        self.app_state.save();
+        self.message = match self.store.save(&self.app_state) {
+            Ok(()) => {
+                self.dirty = false;
+                self.last_edit = None;
+                Some(String::from("Saved"))
+            }
+            Err(e) => {
+                // Don't retry on every tick, wait for the next edit instead.
+                self.last_edit = None;
+                Some(format!("Save failed: {}", e))
+            }
+        };
     }
 }
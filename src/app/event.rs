@@ -1,14 +1,25 @@
-use crossterm::event::KeyEvent;
+use crate::git::{Commit, RepoStatus};
+use crossterm::event::{KeyEvent, MouseEvent};
 
 #[derive(Debug)]
 pub enum ActionPayload {
 	Selection(String, Option<usize>),
 	TextSelection(String, String),
 	Text(String),
+	/// The git status of the directories of projects, by identifier.
+	RepoStatuses(Vec<(String, Result<RepoStatus, String>)>),
+	/// The commits of a branch, by directory and branch name, `None` if
+	/// the branch doesn't exist yet.
+	BranchCommits((String, String), Result<Option<Vec<Commit>>, String>),
+	/// A branch checked out, with whether it was created.
+	CheckedOut(String, Result<bool, String>),
 }
 
 pub enum Event {
 	Input(KeyEvent),
+	Mouse(MouseEvent),
+	/// The terminal's new width and height.
+	Resize(u16, u16),
 	Tick,
 	Action(ActionPayload),
 }
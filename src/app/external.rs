@@ -0,0 +1,62 @@
+use failure::{bail, format_err, Error};
+use std::env;
+use std::fs;
+use std::process::Command;
+use uuid::Uuid;
+
+const DEFAULT_EDITOR: &str = "vi";
+
+/// What an external edit is for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExternalTarget {
+    /// The note at an index of the todo with an identifier.
+    Note(String, usize),
+    /// The description of the todo with an identifier.
+    Description(String),
+}
+
+/// Text the app wants edited in `$EDITOR`. The app only asks for it, the
+/// main loop owns the terminal and suspends the ui while the editor runs.
+#[derive(Debug, Clone)]
+pub struct ExternalEdit {
+    pub target: ExternalTarget,
+    pub content: String,
+}
+
+/// The editor to run: `$VISUAL`, `$EDITOR` or vi, with any arguments given
+/// in the variable, e.g. `code --wait`.
+fn editor() -> Vec<String> {
+    let command = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|command| !command.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    command.split_whitespace().map(String::from).collect()
+}
+
+/// Lets the user edit `content` in their editor through a temporary file
+/// and returns the result. The final line break editors add is dropped
+/// again unless the text had one.
+pub fn edit(content: &str) -> Result<String, Error> {
+    let path = env::temp_dir().join(format!("code_pal-{}.md", Uuid::new_v4()));
+    fs::write(&path, content)?;
+    let editor = editor();
+    let status = Command::new(&editor[0])
+        .args(&editor[1..])
+        .arg(&path)
+        .status();
+    let edited = fs::read_to_string(&path);
+    fs::remove_file(&path).unwrap_or_default();
+    let status = status.map_err(|e| format_err!("couldn't run {}: {}", editor[0], e))?;
+    if !status.success() {
+        bail!("{} exited with {}", editor[0], status);
+    }
+    let mut edited = edited?;
+    if edited.ends_with('\n') && !content.ends_with('\n') {
+        edited.pop();
+        if edited.ends_with('\r') {
+            edited.pop();
+        }
+    }
+    Ok(edited)
+}
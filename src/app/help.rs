@@ -0,0 +1,192 @@
+use crate::app::keymap::{Action, Context, Keymap};
+use crate::app::modal::NORMAL_KEYS;
+use crate::app::theme::Theme;
+use crate::app::ui_component::{Clear, UIComponent, UIEventProcessor};
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
+use tui::Frame;
+
+/// A titled group of keys and what they do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HelpSection {
+    pub title: String,
+    pub keys: Vec<(String, String)>,
+}
+
+/// The keys the text editors handle themselves rather than the keymap.
+const EDITING_KEYS: [(&str, &str); 7] = [
+    ("left, right", "move the cursor"),
+    ("ctrl/alt+left/right", "move by word"),
+    ("home, end", "go to the start or the end"),
+    ("shift+any of the above", "select"),
+    ("backspace, delete", "delete a character"),
+    ("ctrl/alt+backspace", "delete the previous word"),
+    ("ctrl/alt+delete", "delete the next word"),
+];
+
+/// The answers to the question about unsaved changes on quitting.
+const QUIT_KEYS: [(&str, &str); 3] = [
+    ("s, y", "save and quit"),
+    ("d, n", "quit without saving"),
+    ("esc", "don't quit"),
+];
+
+/// The keys of this overlay, besides the ones closing it.
+const HELP_KEYS: [(&str, &str); 4] = [
+    ("up, down, k, j", "scroll"),
+    ("pageup, pagedown, space", "scroll a page"),
+    ("home", "back to the top"),
+    ("q", "close the help"),
+];
+
+fn listed(keys: &[(&str, &str)]) -> Vec<(String, String)> {
+    keys.iter()
+        .map(|(keys, description)| (keys.to_string(), description.to_string()))
+        .collect()
+}
+
+fn section(title: &str, keys: &[(&str, &str)]) -> HelpSection {
+    HelpSection {
+        title: title.to_string(),
+        keys: listed(keys),
+    }
+}
+
+/// The help for the keys as they are bound, followed by the keys the
+/// editors, the question on quitting and the help itself handle on their
+/// own. Actions without keys are left out.
+pub fn sections(keymap: &Keymap, modal: bool) -> Vec<HelpSection> {
+    let mut sections: Vec<_> = Context::ALL
+        .iter()
+        .map(|context| HelpSection {
+            title: context.title().to_string(),
+            keys: context
+                .actions()
+                .iter()
+                .filter(|action| !keymap.keys(*context, **action).is_empty())
+                .map(|action| {
+                    let keys: Vec<_> = keymap
+                        .keys(*context, *action)
+                        .iter()
+                        .map(|key| key.to_string())
+                        .collect();
+                    (keys.join(", "), action.description().to_string())
+                })
+                .collect(),
+        })
+        .collect();
+    if let Some(text) = sections
+        .iter_mut()
+        .find(|section| section.title == Context::Text.title())
+    {
+        text.keys.extend(listed(&EDITING_KEYS));
+    }
+    sections.push(section("Asked about unsaved changes", &QUIT_KEYS));
+    let mut help = section("This help", &HELP_KEYS);
+    for action in &[Action::Help, Action::Cancel] {
+        for key in keymap.keys(Context::Global, *action) {
+            help.keys.last_mut().unwrap().0 += &format!(", {}", key);
+        }
+    }
+    sections.push(help);
+    if modal {
+        let keys: Vec<_> = NORMAL_KEYS
+            .iter()
+            .map(|(keys, _, description)| (*keys, *description))
+            .collect();
+        sections.insert(1, section("Normal mode of the todo list and notes", &keys));
+    }
+    sections
+}
+
+/// An overlay listing the keys, scrolled with the arrow keys.
+pub struct Help {
+    pub title: String,
+    pub sections: Vec<HelpSection>,
+    pub scroll: u16,
+    height: u16,
+}
+
+impl Help {
+    pub fn new(title: String) -> Help {
+        Help {
+            title,
+            sections: vec![],
+            scroll: 0,
+            height: 0,
+        }
+    }
+
+    pub fn show(&mut self, sections: Vec<HelpSection>) {
+        self.sections = sections;
+        self.scroll = 0;
+    }
+
+    fn text(&self, theme: &Theme) -> Vec<Text<'static>> {
+        let width = self
+            .sections
+            .iter()
+            .flat_map(|section| section.keys.iter())
+            .map(|(keys, _)| keys.chars().count())
+            .max()
+            .unwrap_or(0);
+        let title_style = theme.header;
+        let key_style = theme.accent;
+        let mut text = vec![];
+        for section in &self.sections {
+            text.push(Text::styled(format!("{}\n", section.title), title_style));
+            for (keys, description) in &section.keys {
+                text.push(Text::styled(format!("  {:1$}", keys, width), key_style));
+                text.push(Text::raw(format!("  {}\n", description)));
+            }
+            text.push(Text::raw("\n"));
+        }
+        text
+    }
+
+    fn line_count(&self) -> u16 {
+        self.sections
+            .iter()
+            .map(|section| section.keys.len() + 2)
+            .sum::<usize>() as u16
+    }
+}
+
+impl UIComponent for Help {
+    fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
+    where
+        B: Backend,
+    {
+        self.height = area.height.saturating_sub(2);
+        self.scroll = self
+            .scroll
+            .min(self.line_count().saturating_sub(self.height));
+        Clear.render(f, area);
+        let text = self.text(theme);
+        Paragraph::new(text.iter())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(&self.title)
+                    .title_style(theme.title),
+            )
+            .scroll(self.scroll)
+            .render(f, area);
+    }
+}
+
+impl UIEventProcessor for Help {
+    fn on_event(&mut self, event: KeyEvent) {
+        let page = self.height.max(1);
+        match event.code {
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll += page,
+            KeyCode::Home => self.scroll = 0,
+            _ => {}
+        }
+    }
+}
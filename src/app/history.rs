@@ -0,0 +1,365 @@
+use std::any::Any;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+const HISTORY_LIMIT: usize = 500;
+
+/// A reversible mutation of the state shared with the editors.
+pub trait Command {
+    fn apply(&mut self);
+    fn revert(&mut self);
+    /// The row the command touches, used to bring it back into view.
+    fn index(&self) -> usize;
+    /// Whether the command changes the row at `index` rather than adding or
+    /// removing it.
+    fn in_place(&self) -> bool {
+        true
+    }
+    /// Folds `next` into this command so that both are undone in one step.
+    fn merge(&mut self, _next: &dyn Command) -> bool {
+        false
+    }
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Where a command happened, so undo and redo can select the affected row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Focus {
+    TodoItems(usize),
+    Notes(String, usize),
+    Projects(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EditKind {
+    Typing,
+    Deleting,
+    Other,
+}
+
+/// Replaces `removed` with `inserted` at byte `position` of one text field of
+/// the row at `index`.
+pub struct TextEdit<T> {
+    pub list: Rc<RefCell<Vec<T>>>,
+    pub index: usize,
+    pub field: usize,
+    pub accessor: fn(&mut T, usize) -> &mut String,
+    pub position: usize,
+    pub removed: String,
+    pub inserted: String,
+    pub kind: EditKind,
+}
+
+/// Replaces `remove` at byte `position` of `text` with `insert`, if it's
+/// still there.
+fn splice_text(text: &mut String, position: usize, remove: &str, insert: &str) {
+    let end = position + remove.len();
+    if text.get(position..end) == Some(remove) {
+        text.replace_range(position..end, insert);
+    }
+}
+
+fn ends_word(text: &str, next: &str) -> bool {
+    let last = text.chars().last().is_some_and(char::is_whitespace);
+    let first = next.chars().next().is_some_and(char::is_whitespace);
+    last && !first
+}
+
+/// The text change of an edit command.
+struct Splice<'a> {
+    position: &'a mut usize,
+    removed: &'a mut String,
+    inserted: &'a mut String,
+}
+
+impl<'a> Splice<'a> {
+    /// Folds the change `next` (position, removed, inserted) into this one
+    /// when both are typing or deleting within the same word.
+    fn merge(self, kind: EditKind, next: (usize, &str, &str)) -> bool {
+        let (position, removed, inserted) = next;
+        match kind {
+            EditKind::Typing
+                if position == *self.position + self.inserted.len()
+                    && !ends_word(self.inserted, inserted) =>
+            {
+                self.inserted.push_str(inserted);
+                true
+            }
+            EditKind::Deleting
+                if position + removed.len() == *self.position
+                    && !ends_word(removed, self.removed) =>
+            {
+                *self.position = position;
+                self.removed.insert_str(0, removed);
+                true
+            }
+            EditKind::Deleting
+                if position == *self.position && !ends_word(self.removed, removed) =>
+            {
+                self.removed.push_str(removed);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl<T: 'static> TextEdit<T> {
+    fn splice(&mut self, remove: &str, insert: &str) {
+        let mut list = self.list.borrow_mut();
+        if let Some(item) = list.get_mut(self.index) {
+            splice_text(
+                (self.accessor)(item, self.field),
+                self.position,
+                remove,
+                insert,
+            );
+        }
+    }
+}
+
+impl<T: 'static> Command for TextEdit<T> {
+    fn apply(&mut self) {
+        let (removed, inserted) = (self.removed.clone(), self.inserted.clone());
+        self.splice(&removed, &inserted);
+    }
+    fn revert(&mut self) {
+        let (removed, inserted) = (self.removed.clone(), self.inserted.clone());
+        self.splice(&inserted, &removed);
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next = match next.as_any().downcast_ref::<TextEdit<T>>() {
+            Some(next) => next,
+            None => return false,
+        };
+        if !Rc::ptr_eq(&self.list, &next.list)
+            || self.index != next.index
+            || self.field != next.field
+            || self.kind != next.kind
+        {
+            return false;
+        }
+        let splice = Splice {
+            position: &mut self.position,
+            removed: &mut self.removed,
+            inserted: &mut self.inserted,
+        };
+        splice.merge(self.kind, (next.position, &next.removed, &next.inserted))
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Like `TextEdit`, for text kept as lines: replaces `removed` with
+/// `inserted` at byte `position` of the lines of the row at `index` joined
+/// by line breaks.
+pub struct LinesEdit<T> {
+    pub list: Rc<RefCell<Vec<T>>>,
+    pub index: usize,
+    pub accessor: fn(&mut T) -> &mut Vec<String>,
+    pub position: usize,
+    pub removed: String,
+    pub inserted: String,
+    pub kind: EditKind,
+}
+
+impl<T: 'static> LinesEdit<T> {
+    fn splice(&mut self, remove: &str, insert: &str) {
+        let mut list = self.list.borrow_mut();
+        if let Some(item) = list.get_mut(self.index) {
+            let lines = (self.accessor)(item);
+            let mut text = lines.join("\n");
+            splice_text(&mut text, self.position, remove, insert);
+            *lines = match text.as_str() {
+                "" => vec![],
+                text => text.split('\n').map(String::from).collect(),
+            };
+        }
+    }
+}
+
+impl<T: 'static> Command for LinesEdit<T> {
+    fn apply(&mut self) {
+        let (removed, inserted) = (self.removed.clone(), self.inserted.clone());
+        self.splice(&removed, &inserted);
+    }
+    fn revert(&mut self) {
+        let (removed, inserted) = (self.removed.clone(), self.inserted.clone());
+        self.splice(&inserted, &removed);
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+    fn merge(&mut self, next: &dyn Command) -> bool {
+        let next = match next.as_any().downcast_ref::<LinesEdit<T>>() {
+            Some(next) => next,
+            None => return false,
+        };
+        if !Rc::ptr_eq(&self.list, &next.list) || self.index != next.index || self.kind != next.kind
+        {
+            return false;
+        }
+        let splice = Splice {
+            position: &mut self.position,
+            removed: &mut self.removed,
+            inserted: &mut self.inserted,
+        };
+        splice.merge(self.kind, (next.position, &next.removed, &next.inserted))
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Inserts `item` at `index`.
+pub struct InsertRow<T> {
+    pub list: Rc<RefCell<Vec<T>>>,
+    pub index: usize,
+    pub item: Option<T>,
+}
+
+impl<T: 'static> Command for InsertRow<T> {
+    fn apply(&mut self) {
+        if let Some(item) = self.item.take() {
+            let mut list = self.list.borrow_mut();
+            let index = self.index.min(list.len());
+            list.insert(index, item);
+        }
+    }
+    fn revert(&mut self) {
+        let mut list = self.list.borrow_mut();
+        if self.index < list.len() {
+            self.item = Some(list.remove(self.index));
+        }
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+    fn in_place(&self) -> bool {
+        false
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Removes the row at `index`, keeping it so it can be put back.
+pub struct RemoveRow<T> {
+    pub list: Rc<RefCell<Vec<T>>>,
+    pub index: usize,
+    pub item: Option<T>,
+}
+
+impl<T: 'static> Command for RemoveRow<T> {
+    fn apply(&mut self) {
+        let mut list = self.list.borrow_mut();
+        if self.index < list.len() {
+            self.item = Some(list.remove(self.index));
+        }
+    }
+    fn revert(&mut self) {
+        if let Some(item) = self.item.take() {
+            let mut list = self.list.borrow_mut();
+            let index = self.index.min(list.len());
+            list.insert(index, item);
+        }
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+    fn in_place(&self) -> bool {
+        false
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Swaps the field of the row at `index` with `value`, so the same command
+/// sets the new value and puts the old one back.
+pub struct SetField<T, V> {
+    pub list: Rc<RefCell<Vec<T>>>,
+    pub index: usize,
+    pub accessor: fn(&mut T) -> &mut V,
+    pub value: V,
+}
+
+impl<T: 'static, V: 'static> SetField<T, V> {
+    fn swap(&mut self) {
+        if let Some(item) = self.list.borrow_mut().get_mut(self.index) {
+            std::mem::swap((self.accessor)(item), &mut self.value);
+        }
+    }
+}
+
+impl<T: 'static, V: 'static> Command for SetField<T, V> {
+    fn apply(&mut self) {
+        self.swap();
+    }
+    fn revert(&mut self) {
+        self.swap();
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+pub struct Entry {
+    pub command: Box<dyn Command>,
+    pub focus: Focus,
+}
+
+/// The undo and redo stacks of the whole app.
+#[derive(Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// Records an already applied command.
+    pub fn record(&mut self, command: Box<dyn Command>, focus: Focus) {
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut() {
+            if last.focus == focus && last.command.merge(command.as_ref()) {
+                return;
+            }
+        }
+        self.undo.push(Entry { command, focus });
+        if self.undo.len() > HISTORY_LIMIT {
+            self.undo.remove(0);
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<Focus> {
+        let mut entry = self.undo.pop()?;
+        entry.command.revert();
+        let focus = entry.focus.clone();
+        self.redo.push(entry);
+        Some(focus)
+    }
+
+    pub fn redo(&mut self) -> Option<Focus> {
+        let mut entry = self.redo.pop()?;
+        entry.command.apply();
+        let focus = entry.focus.clone();
+        self.undo.push(entry);
+        Some(focus)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
@@ -0,0 +1,723 @@
+use crate::config::KeysConfig;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use failure::{bail, format_err, Error};
+use std::fmt;
+use std::str::FromStr;
+
+/// A key together with the modifiers held with it, e.g. `ctrl+s`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyChord {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+        // Terminals send shifted characters and ctrl+i, ctrl+m, ctrl+j and
+        // ctrl+[ as other keys, so chords are kept the way they arrive.
+        let (code, modifiers) = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+                KeyCode::Char(c.to_ascii_uppercase()),
+                modifiers - KeyModifiers::SHIFT,
+            ),
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                match c.to_ascii_lowercase() {
+                    'i' => (KeyCode::Tab, modifiers - KeyModifiers::CONTROL),
+                    'm' | 'j' => (KeyCode::Enter, modifiers - KeyModifiers::CONTROL),
+                    '[' => (KeyCode::Esc, modifiers - KeyModifiers::CONTROL),
+                    c => (KeyCode::Char(c), modifiers),
+                }
+            }
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => {
+                (KeyCode::BackTab, modifiers - KeyModifiers::SHIFT)
+            }
+            KeyCode::BackTab => (code, modifiers - KeyModifiers::SHIFT),
+            _ => (code, modifiers),
+        };
+        KeyChord { code, modifiers }
+    }
+}
+
+impl From<KeyEvent> for KeyChord {
+    fn from(event: KeyEvent) -> KeyChord {
+        KeyChord::new(event.code, event.modifiers)
+    }
+}
+
+const KEY_NAMES: [(&str, KeyCode); 15] = [
+    ("enter", KeyCode::Enter),
+    ("esc", KeyCode::Esc),
+    ("tab", KeyCode::Tab),
+    ("backtab", KeyCode::BackTab),
+    ("backspace", KeyCode::Backspace),
+    ("delete", KeyCode::Delete),
+    ("insert", KeyCode::Insert),
+    ("up", KeyCode::Up),
+    ("down", KeyCode::Down),
+    ("left", KeyCode::Left),
+    ("right", KeyCode::Right),
+    ("home", KeyCode::Home),
+    ("end", KeyCode::End),
+    ("pageup", KeyCode::PageUp),
+    ("pagedown", KeyCode::PageDown),
+];
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    let lower = name.to_ascii_lowercase();
+    if lower == "space" {
+        return Some(KeyCode::Char(' '));
+    }
+    if let Some(number) = lower.strip_prefix('f').and_then(|n| n.parse().ok()) {
+        return Some(KeyCode::F(number)).filter(|_| (1..=12).contains(&number));
+    }
+    KEY_NAMES
+        .iter()
+        .find(|(key, _)| *key == lower)
+        .map(|(_, code)| *code)
+}
+
+impl FromStr for KeyChord {
+    type Err = Error;
+
+    /// Parses chords such as `ctrl+s`, `alt+shift+p`, `esc`, `pagedown`,
+    /// `f2` or `?`.
+    fn from_str(s: &str) -> Result<KeyChord, Error> {
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => match s.rfind('+') {
+                Some(i) if i + 1 < s.len() => (&s[..i], &s[i + 1..]),
+                _ => ("", s),
+            },
+        };
+        let mut held = KeyModifiers::empty();
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            held |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" | "meta" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => bail!("unknown modifier {:?} in {:?}", modifier, s),
+            };
+        }
+        let code = parse_key(key).ok_or_else(|| format_err!("unknown key {:?} in {:?}", key, s))?;
+        Ok(KeyChord::new(code, held))
+    }
+}
+
+impl fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            write!(f, "shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(number) => write!(f, "f{}", number),
+            code => match KEY_NAMES.iter().find(|(_, key)| *key == code) {
+                Some((name, _)) => write!(f, "{}", name),
+                None => write!(f, "{:?}", code),
+            },
+        }
+    }
+}
+
+/// Where a binding applies. Global bindings apply everywhere, text ones
+/// whenever text is edited, the others while the matching part of the ui
+/// has the focus. The more specific take precedence.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Context {
+    Global,
+    TodoList,
+    Notes,
+    Projects,
+    AutoComplete,
+    Text,
+}
+
+impl Context {
+    pub const ALL: [Context; 6] = [
+        Context::Global,
+        Context::TodoList,
+        Context::Notes,
+        Context::Projects,
+        Context::AutoComplete,
+        Context::Text,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Context::Global => "global",
+            Context::TodoList => "todo_list",
+            Context::Notes => "notes",
+            Context::Projects => "projects",
+            Context::AutoComplete => "autocomplete",
+            Context::Text => "text",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            Context::Global => "Everywhere",
+            Context::TodoList => "Todo list",
+            Context::Notes => "Notes",
+            Context::Projects => "Projects table",
+            Context::AutoComplete => "Project and workspace pickers",
+            Context::Text => "While editing text",
+        }
+    }
+
+    /// The actions that can be bound in this context.
+    pub fn actions(self) -> &'static [Action] {
+        use Action::*;
+        match self {
+            Context::Global => &[
+                Quit,
+                AddTodo,
+                AddNote,
+                EditNote,
+                EditDescription,
+                Project,
+                Save,
+                SelectWorkspace,
+                Filter,
+                Search,
+                CycleSort,
+                ToggleGrouping,
+                Undo,
+                Redo,
+                OpenInEditor,
+                Cancel,
+                NextTab,
+                PreviousTab,
+                WidenList,
+                NarrowList,
+                GrowTodos,
+                ShrinkTodos,
+                ResetLayout,
+                Help,
+            ],
+            Context::TodoList => &[
+                CycleStatus,
+                CyclePriority,
+                NextDue,
+                SetDue,
+                Branch,
+                Up,
+                Down,
+                Next,
+                PageUp,
+                PageDown,
+                First,
+                Last,
+                InsertRow,
+                InsertRowAbove,
+                DeleteRow,
+            ],
+            Context::Notes => &[
+                Up,
+                Down,
+                Next,
+                PageUp,
+                PageDown,
+                First,
+                Last,
+                InsertRow,
+                InsertRowAbove,
+                DeleteRow,
+            ],
+            Context::Projects => &[
+                Up,
+                Down,
+                PageUp,
+                PageDown,
+                First,
+                Last,
+                NextColumn,
+                PreviousColumn,
+            ],
+            Context::AutoComplete => &[Up, Down, PageUp, PageDown, First, Last, Choose],
+            Context::Text => &[Copy, Cut, Paste, DeleteWord, DeleteChar, TogglePreview],
+        }
+    }
+}
+
+impl FromStr for Context {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Context, Error> {
+        Context::ALL
+            .iter()
+            .find(|context| context.name() == s)
+            .cloned()
+            .ok_or_else(|| format_err!("unknown key context {:?}", s))
+    }
+}
+
+/// Something a key can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Quit,
+    AddTodo,
+    AddNote,
+    EditNote,
+    EditDescription,
+    /// Picks the project of the todo, or adds one on the projects tab.
+    Project,
+    Save,
+    SelectWorkspace,
+    Filter,
+    Search,
+    CycleSort,
+    ToggleGrouping,
+    Undo,
+    Redo,
+    OpenInEditor,
+    Cancel,
+    /// Switches tabs, only while nothing is being edited.
+    NextTab,
+    PreviousTab,
+    /// Moves the border between the lists and the panes beside them.
+    WidenList,
+    NarrowList,
+    /// Moves the border below the todo list.
+    GrowTodos,
+    ShrinkTodos,
+    ResetLayout,
+    /// Shows the keys, plain characters like `?` only work while not typing.
+    Help,
+    CycleStatus,
+    CyclePriority,
+    NextDue,
+    /// Asks for the due date.
+    SetDue,
+    /// Names the git branch of the todo, then checks it out.
+    Branch,
+    Up,
+    /// Moves down, adding a row after the last one.
+    Down,
+    /// Moves down, stopping at the last row.
+    Next,
+    /// Moves by as many rows as the pane shows.
+    PageUp,
+    PageDown,
+    First,
+    Last,
+    InsertRow,
+    InsertRowAbove,
+    /// Removes the selected row, whatever it holds.
+    DeleteRow,
+    NextColumn,
+    PreviousColumn,
+    Choose,
+    Copy,
+    Cut,
+    Paste,
+    DeleteWord,
+    /// Deletes backwards, for terminals sending ctrl+h for backspace.
+    DeleteChar,
+    /// Switches the note and description pane between editing and the
+    /// rendered markdown.
+    TogglePreview,
+}
+
+impl Action {
+    /// Whether the action belongs to the text context, so that whatever
+    /// edits text handles it.
+    pub fn edits_text(self) -> bool {
+        Context::Text.actions().contains(&self)
+    }
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::AddTodo => "add_todo",
+            Action::AddNote => "add_note",
+            Action::EditNote => "edit_note",
+            Action::EditDescription => "edit_description",
+            Action::Project => "project",
+            Action::Save => "save",
+            Action::SelectWorkspace => "select_workspace",
+            Action::Filter => "filter",
+            Action::Search => "search",
+            Action::CycleSort => "cycle_sort",
+            Action::ToggleGrouping => "toggle_grouping",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::OpenInEditor => "open_in_editor",
+            Action::Cancel => "cancel",
+            Action::NextTab => "next_tab",
+            Action::PreviousTab => "previous_tab",
+            Action::WidenList => "widen_list",
+            Action::NarrowList => "narrow_list",
+            Action::GrowTodos => "grow_todos",
+            Action::ShrinkTodos => "shrink_todos",
+            Action::ResetLayout => "reset_layout",
+            Action::Help => "help",
+            Action::CycleStatus => "cycle_status",
+            Action::CyclePriority => "cycle_priority",
+            Action::NextDue => "next_due",
+            Action::SetDue => "set_due",
+            Action::Branch => "branch",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Next => "next",
+            Action::PageUp => "page_up",
+            Action::PageDown => "page_down",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::InsertRow => "insert_row",
+            Action::InsertRowAbove => "insert_row_above",
+            Action::DeleteRow => "delete_row",
+            Action::NextColumn => "next_column",
+            Action::PreviousColumn => "previous_column",
+            Action::Choose => "choose",
+            Action::Copy => "copy",
+            Action::Cut => "cut",
+            Action::Paste => "paste",
+            Action::DeleteWord => "delete_word",
+            Action::DeleteChar => "delete_char",
+            Action::TogglePreview => "toggle_preview",
+        }
+    }
+
+    /// What the help says the action does.
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "quit, asking about unsaved changes",
+            Action::AddTodo => "edit the todo list",
+            Action::AddNote => "edit the notes of the todo",
+            Action::EditNote => "edit the selected note in the note pane",
+            Action::EditDescription => "edit the description of the todo",
+            Action::Project => "pick the project of the todo, or edit the projects",
+            Action::Save => "save",
+            Action::SelectWorkspace => "switch workspaces",
+            Action::Filter => "filter the todo list",
+            Action::Search => "search todos, notes and projects",
+            Action::CycleSort => "change the sort order",
+            Action::ToggleGrouping => "group todos by project",
+            Action::Undo => "undo",
+            Action::Redo => "redo",
+            Action::OpenInEditor => "open the note or description in $EDITOR",
+            Action::Cancel => "stop what you're doing",
+            Action::NextTab => "next tab, while not editing",
+            Action::PreviousTab => "previous tab, while not editing",
+            Action::WidenList => "widen the todo list and notes",
+            Action::NarrowList => "narrow the todo list and notes",
+            Action::GrowTodos => "make the todo list taller",
+            Action::ShrinkTodos => "make the todo list shorter",
+            Action::ResetLayout => "split the panes as by default",
+            Action::Help => "show the keys",
+            Action::CycleStatus => "change the status",
+            Action::CyclePriority => "change the priority",
+            Action::NextDue => "change the due date",
+            Action::SetDue => "type the due date",
+            Action::Branch => "set the branch of the todo and check it out",
+            Action::Up => "move up",
+            Action::Down => "move down, adding a row after the last",
+            Action::Next => "move down",
+            Action::PageUp => "move up a page",
+            Action::PageDown => "move down a page",
+            Action::First => "go to the first row",
+            Action::Last => "go to the last row",
+            Action::InsertRow => "add a row below",
+            Action::InsertRowAbove => "add a row above",
+            Action::DeleteRow => "delete the row",
+            Action::NextColumn => "next column",
+            Action::PreviousColumn => "previous column",
+            Action::Choose => "pick the selected suggestion",
+            Action::Copy => "copy the selection",
+            Action::Cut => "cut the selection",
+            Action::Paste => "paste",
+            Action::DeleteWord => "delete the previous word",
+            Action::DeleteChar => "delete the previous character",
+            Action::TogglePreview => "switch the note pane between editing and reading",
+        }
+    }
+}
+
+/// A starting set of bindings that the config file can then change.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Preset {
+    Default,
+    /// ctrl+p/ctrl+n move in lists, ctrl+s searches, and the shortcuts
+    /// taken by emacs line editing move to alt.
+    Emacs,
+    /// alt+j/alt+k move in lists and alt+h/alt+l switch tabs.
+    Vim,
+}
+
+type Bindings = &'static [(Context, Action, &'static [&'static str])];
+
+const DEFAULT_BINDINGS: Bindings = &[
+    (Context::Global, Action::Quit, &["ctrl+q"]),
+    (Context::Global, Action::AddTodo, &["ctrl+a"]),
+    (Context::Global, Action::AddNote, &["ctrl+n"]),
+    (Context::Global, Action::EditNote, &["ctrl+e"]),
+    (Context::Global, Action::EditDescription, &["ctrl+d"]),
+    (Context::Global, Action::Project, &["ctrl+p"]),
+    (Context::Global, Action::Save, &["ctrl+s"]),
+    (Context::Global, Action::SelectWorkspace, &["ctrl+o"]),
+    (Context::Global, Action::Filter, &["ctrl+l"]),
+    (Context::Global, Action::Search, &["ctrl+f"]),
+    (Context::Global, Action::CycleSort, &["ctrl+t"]),
+    (Context::Global, Action::ToggleGrouping, &["ctrl+g"]),
+    (Context::Global, Action::Undo, &["ctrl+z"]),
+    (Context::Global, Action::Redo, &["ctrl+y"]),
+    (Context::Global, Action::OpenInEditor, &["alt+e"]),
+    (Context::Global, Action::Cancel, &["esc"]),
+    (Context::Global, Action::NextTab, &["right"]),
+    (Context::Global, Action::PreviousTab, &["left"]),
+    (Context::Global, Action::WidenList, &["alt+right"]),
+    (Context::Global, Action::NarrowList, &["alt+left"]),
+    (Context::Global, Action::GrowTodos, &["alt+down"]),
+    (Context::Global, Action::ShrinkTodos, &["alt+up"]),
+    (Context::Global, Action::ResetLayout, &["alt+0"]),
+    (Context::Global, Action::Help, &["?", "f1"]),
+    (Context::TodoList, Action::CycleStatus, &["alt+s"]),
+    (Context::TodoList, Action::CyclePriority, &["alt+p"]),
+    (Context::TodoList, Action::NextDue, &["alt+d"]),
+    (Context::TodoList, Action::SetDue, &["alt+shift+d"]),
+    (Context::TodoList, Action::Branch, &["alt+b"]),
+    (Context::TodoList, Action::Up, &["up"]),
+    (Context::TodoList, Action::Down, &["down"]),
+    (Context::TodoList, Action::PageUp, &["pageup"]),
+    (Context::TodoList, Action::PageDown, &["pagedown"]),
+    (Context::TodoList, Action::First, &["ctrl+home"]),
+    (Context::TodoList, Action::Last, &["ctrl+end"]),
+    (Context::TodoList, Action::InsertRow, &["enter"]),
+    (Context::Notes, Action::Up, &["up"]),
+    (Context::Notes, Action::Down, &["down"]),
+    (Context::Notes, Action::PageUp, &["pageup"]),
+    (Context::Notes, Action::PageDown, &["pagedown"]),
+    (Context::Notes, Action::First, &["ctrl+home"]),
+    (Context::Notes, Action::Last, &["ctrl+end"]),
+    (Context::Notes, Action::InsertRow, &["enter"]),
+    (Context::Projects, Action::Up, &["up"]),
+    (Context::Projects, Action::Down, &["down"]),
+    (Context::Projects, Action::PageUp, &["pageup"]),
+    (Context::Projects, Action::PageDown, &["pagedown"]),
+    (Context::Projects, Action::First, &["ctrl+home"]),
+    (Context::Projects, Action::Last, &["ctrl+end"]),
+    (Context::Projects, Action::NextColumn, &["tab"]),
+    (Context::Projects, Action::PreviousColumn, &["backtab"]),
+    (Context::AutoComplete, Action::Up, &["up"]),
+    (Context::AutoComplete, Action::Down, &["down"]),
+    (Context::AutoComplete, Action::PageUp, &["pageup"]),
+    (Context::AutoComplete, Action::PageDown, &["pagedown"]),
+    (Context::AutoComplete, Action::First, &["ctrl+home"]),
+    (Context::AutoComplete, Action::Last, &["ctrl+end"]),
+    (Context::AutoComplete, Action::Choose, &["enter"]),
+    (Context::Text, Action::Copy, &["ctrl+c"]),
+    (Context::Text, Action::Cut, &["ctrl+x"]),
+    (Context::Text, Action::Paste, &["ctrl+v"]),
+    (Context::Text, Action::DeleteWord, &["ctrl+w"]),
+    (Context::Text, Action::DeleteChar, &["ctrl+h"]),
+    (Context::Text, Action::TogglePreview, &["ctrl+r"]),
+];
+
+const EMACS_BINDINGS: Bindings = &[
+    (Context::Global, Action::AddTodo, &["alt+a"]),
+    (Context::Global, Action::AddNote, &["alt+n"]),
+    (Context::Global, Action::EditNote, &["alt+m"]),
+    (Context::Global, Action::EditDescription, &["alt+i"]),
+    (Context::Global, Action::Project, &["alt+r"]),
+    (Context::Global, Action::Save, &["alt+w"]),
+    (Context::Global, Action::Search, &["ctrl+s"]),
+    (Context::TodoList, Action::Up, &["up", "ctrl+p"]),
+    (Context::TodoList, Action::Down, &["down", "ctrl+n"]),
+    (Context::Notes, Action::Up, &["up", "ctrl+p"]),
+    (Context::Notes, Action::Down, &["down", "ctrl+n"]),
+    (Context::Projects, Action::Up, &["up", "ctrl+p"]),
+    (Context::Projects, Action::Down, &["down", "ctrl+n"]),
+    (Context::AutoComplete, Action::Up, &["up", "ctrl+p"]),
+    (Context::AutoComplete, Action::Down, &["down", "ctrl+n"]),
+];
+
+const VIM_BINDINGS: Bindings = &[
+    (Context::Global, Action::NextTab, &["right", "alt+l"]),
+    (Context::Global, Action::PreviousTab, &["left", "alt+h"]),
+    (Context::TodoList, Action::Up, &["up", "alt+k"]),
+    (Context::TodoList, Action::Down, &["down", "alt+j"]),
+    (Context::Notes, Action::Up, &["up", "alt+k"]),
+    (Context::Notes, Action::Down, &["down", "alt+j"]),
+    (Context::Projects, Action::Up, &["up", "alt+k"]),
+    (Context::Projects, Action::Down, &["down", "alt+j"]),
+    (Context::AutoComplete, Action::Up, &["up", "alt+k"]),
+    (Context::AutoComplete, Action::Down, &["down", "alt+j"]),
+];
+
+impl Preset {
+    fn bindings(self) -> Bindings {
+        match self {
+            Preset::Default => &[],
+            Preset::Emacs => EMACS_BINDINGS,
+            Preset::Vim => VIM_BINDINGS,
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Preset, Error> {
+        match s {
+            "default" => Ok(Preset::Default),
+            "emacs" => Ok(Preset::Emacs),
+            "vim" => Ok(Preset::Vim),
+            _ => bail!("unknown key preset {:?}, expected default, emacs or vim", s),
+        }
+    }
+}
+
+/// Returned when one key is bound to several actions that can apply at
+/// the same time.
+#[derive(Debug)]
+pub struct KeyConflicts(pub Vec<String>);
+
+impl fmt::Display for KeyConflicts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "conflicting key bindings:")?;
+        for conflict in &self.0 {
+            write!(f, "\n  {}", conflict)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for KeyConflicts {}
+
+/// Which key does what, in which context.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<(Context, Action, Vec<KeyChord>)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Keymap {
+        Keymap::from_bindings(DEFAULT_BINDINGS)
+    }
+}
+
+impl Keymap {
+    fn from_bindings(bindings: Bindings) -> Keymap {
+        let mut keymap = Keymap { bindings: vec![] };
+        keymap.bind_all(bindings);
+        keymap
+    }
+
+    fn bind_all(&mut self, bindings: Bindings) {
+        for (context, action, keys) in bindings {
+            let keys = keys.iter().map(|key| key.parse().unwrap()).collect();
+            self.bind(*context, *action, keys);
+        }
+    }
+
+    /// Replaces the keys of `action` in `context`.
+    pub fn bind(&mut self, context: Context, action: Action, keys: Vec<KeyChord>) {
+        match self
+            .bindings
+            .iter_mut()
+            .find(|(c, a, _)| *c == context && *a == action)
+        {
+            Some(binding) => binding.2 = keys,
+            None => self.bindings.push((context, action, keys)),
+        }
+    }
+
+    /// The preset named in the config with the config's own bindings on
+    /// top, failing on anything unknown or conflicting.
+    pub fn from_config(config: &KeysConfig) -> Result<Keymap, Error> {
+        let preset = match &config.preset {
+            Some(preset) => preset.parse()?,
+            None => Preset::Default,
+        };
+        let mut keymap = Keymap::default();
+        keymap.bind_all(preset.bindings());
+        for (context, actions) in &config.contexts {
+            let context: Context = context.parse()?;
+            for (name, keys) in actions {
+                let action = context
+                    .actions()
+                    .iter()
+                    .find(|action| action.name() == name)
+                    .ok_or_else(|| {
+                        format_err!("unknown action {:?} in keys.{}", name, context.name())
+                    })?;
+                let keys = keys
+                    .iter()
+                    .map(|key| key.parse())
+                    .collect::<Result<_, Error>>()?;
+                keymap.bind(context, *action, keys);
+            }
+        }
+        keymap.validate()?;
+        Ok(keymap)
+    }
+
+    /// Fails with every key that is bound twice in a context, or in a
+    /// context and globally or for editing text.
+    pub fn validate(&self) -> Result<(), KeyConflicts> {
+        let mut conflicts = vec![];
+        for (i, (context, action, keys)) in self.bindings.iter().enumerate() {
+            for (other_context, other, other_keys) in &self.bindings[i + 1..] {
+                let everywhere =
+                    |context: &Context| matches!(context, Context::Global | Context::Text);
+                let overlap =
+                    context == other_context || everywhere(context) || everywhere(other_context);
+                for key in keys
+                    .iter()
+                    .filter(|key| overlap && other_keys.contains(key))
+                {
+                    conflicts.push(format!(
+                        "{} is bound to {}.{} and {}.{}",
+                        key,
+                        context.name(),
+                        action.name(),
+                        other_context.name(),
+                        other.name()
+                    ));
+                }
+            }
+        }
+        match conflicts.is_empty() {
+            true => Ok(()),
+            false => Err(KeyConflicts(conflicts)),
+        }
+    }
+
+    /// What `event` does in `context`, falling back to the text and then the
+    /// global bindings.
+    pub fn action(&self, context: Option<Context>, event: KeyEvent) -> Option<Action> {
+        let chord = KeyChord::from(event);
+        let find = |wanted: Context| {
+            self.bindings
+                .iter()
+                .find(|(c, _, keys)| *c == wanted && keys.contains(&chord))
+                .map(|(_, action, _)| *action)
+        };
+        context
+            .and_then(find)
+            .or_else(|| find(Context::Text))
+            .or_else(|| find(Context::Global))
+    }
+
+    /// The keys bound to `action` in `context`.
+    pub fn keys(&self, context: Context, action: Action) -> &[KeyChord] {
+        self.bindings
+            .iter()
+            .find(|(c, a, _)| *c == context && *a == action)
+            .map(|(_, _, keys)| keys.as_slice())
+            .unwrap_or(&[])
+    }
+}
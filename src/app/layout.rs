@@ -0,0 +1,153 @@
+use crate::config::LayoutConfig;
+use failure::{bail, format_err, Error};
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+use tui::layout::{Constraint, Direction, Layout, Rect};
+
+/// How far a resizing key moves a border, in percent.
+pub const RESIZE_STEP: u16 = 5;
+/// No pane gets less than this, or more than what is left by it, in percent.
+pub const MIN_SHARE: u16 = 10;
+
+/// The width of a column of a `TableEditor`, written `20` (columns), `40%`
+/// or `auto` (as wide as its widest cell) in the config.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ColumnWidth {
+    Length(u16),
+    Percentage(u16),
+    Auto,
+}
+
+impl ColumnWidth {
+    /// The constraint for a column whose widest cell is `content` wide, in a
+    /// table `width` wide. An automatic column takes at most half the table.
+    pub fn constraint(self, content: u16, width: u16) -> Constraint {
+        match self {
+            ColumnWidth::Length(length) => Constraint::Length(length),
+            ColumnWidth::Percentage(percent) => Constraint::Percentage(percent),
+            ColumnWidth::Auto => Constraint::Length(content.min(width / 2).max(1)),
+        }
+    }
+}
+
+impl FromStr for ColumnWidth {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<ColumnWidth, Error> {
+        let invalid = || format_err!("invalid column width {:?}, expected auto, 40% or 20", s);
+        if s == "auto" {
+            return Ok(ColumnWidth::Auto);
+        }
+        match s.strip_suffix('%') {
+            Some(percent) => match percent.parse() {
+                Ok(percent) if percent > 0 && percent <= 100 => {
+                    Ok(ColumnWidth::Percentage(percent))
+                }
+                _ => Err(invalid()),
+            },
+            None => match s.parse() {
+                Ok(length) if length > 0 => Ok(ColumnWidth::Length(length)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ColumnWidth {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<ColumnWidth, Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for ColumnWidth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ColumnWidth::Length(length) => write!(f, "{}", length),
+            ColumnWidth::Percentage(percent) => write!(f, "{}%", percent),
+            ColumnWidth::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl From<ColumnWidth> for String {
+    fn from(width: ColumnWidth) -> String {
+        width.to_string()
+    }
+}
+
+/// `share` moved by `delta` percent, keeping both sides of the border at
+/// least `MIN_SHARE` wide.
+pub fn resize(share: u16, delta: i16) -> u16 {
+    let moved = share as i16 + delta;
+    moved.max(MIN_SHARE as i16).min((100 - MIN_SHARE) as i16) as u16
+}
+
+impl LayoutConfig {
+    /// Side by side, or stacked when the terminal is narrow.
+    pub fn direction(&self, width: u16) -> Direction {
+        match width < self.stack_below {
+            true => Direction::Vertical,
+            false => Direction::Horizontal,
+        }
+    }
+
+    /// Fails on a share that would leave a pane too small, or on widths
+    /// that aren't one per column of the projects table.
+    pub fn validate(&self) -> Result<(), Error> {
+        let columns = LayoutConfig::default().project_columns.len();
+        if self.project_columns.len() != columns {
+            bail!(
+                "layout.project_columns has {} widths, expected {}, one per column of the projects table",
+                self.project_columns.len(),
+                columns
+            );
+        }
+        let shares = [
+            ("list_width", self.list_width),
+            ("todo_height", self.todo_height),
+            ("description_height", self.description_height),
+        ];
+        for (name, share) in shares.iter() {
+            if *share < MIN_SHARE || *share > 100 - MIN_SHARE {
+                bail!(
+                    "layout.{} is {}, expected {} to {}",
+                    name,
+                    share,
+                    MIN_SHARE,
+                    100 - MIN_SHARE
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// The shares as they are by default, the columns and the width to
+    /// stack at as they are.
+    pub fn reset(&mut self) {
+        *self = LayoutConfig {
+            project_columns: std::mem::take(&mut self.project_columns),
+            stack_below: self.stack_below,
+            ..LayoutConfig::default()
+        };
+    }
+}
+
+/// `area` split in two along `direction`, `share` percent going to the
+/// first part.
+pub fn split(direction: &Direction, share: u16, area: Rect) -> Vec<Rect> {
+    Layout::default()
+        .direction(direction.clone())
+        .constraints(
+            [
+                Constraint::Percentage(share),
+                Constraint::Percentage(100 - share),
+            ]
+            .as_ref(),
+        )
+        .split(area)
+}
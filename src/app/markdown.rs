@@ -0,0 +1,137 @@
+use crate::app::theme::Theme;
+use tui::style::{Modifier, Style};
+use tui::widgets::Text;
+
+fn heading_style(level: usize, theme: &Theme) -> Style {
+    let header = theme.header;
+    match level {
+        1 => header.modifier(header.modifier | Modifier::BOLD | Modifier::UNDERLINED),
+        2 => header.modifier(header.modifier | Modifier::BOLD),
+        _ => Style::default().modifier(Modifier::BOLD),
+    }
+}
+
+/// The level of a `# heading` line and its text.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    match (level, line[level..].strip_prefix(' ')) {
+        (1..=6, Some(text)) => Some((level, text)),
+        _ => None,
+    }
+}
+
+/// Whether a `- [ ]` or `- [x]` line is checked and its text.
+fn checkbox(line: &str) -> Option<(bool, &str)> {
+    let item = ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| line.strip_prefix(bullet))?;
+    if let Some(text) = item.strip_prefix("[ ] ") {
+        Some((false, text))
+    } else {
+        item.strip_prefix("[x] ")
+            .or_else(|| item.strip_prefix("[X] "))
+            .map(|text| (true, text))
+    }
+}
+
+fn bullet(line: &str) -> Option<&str> {
+    ["- ", "* ", "+ "]
+        .iter()
+        .find_map(|bullet| line.strip_prefix(bullet))
+}
+
+/// The number of a `1. item` line, with its dot, and the item.
+fn numbered(line: &str) -> Option<(&str, &str)> {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    match line[digits..].strip_prefix(". ") {
+        Some(text) if digits > 0 => Some((&line[..digits + 1], text)),
+        _ => None,
+    }
+}
+
+/// Styles `code`, **bold** and *emphasis* within a line.
+fn inline(text: &str, style: Style, theme: &Theme, spans: &mut Vec<Text<'static>>) {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let marked = match c {
+            '`' => rest[1..]
+                .find('`')
+                .map(|end| (&rest[1..=end], end + 2, theme.code)),
+            '*' if rest.starts_with("**") => rest[2..].find("**").map(|end| {
+                let bold = style.modifier(style.modifier | Modifier::BOLD);
+                (&rest[2..end + 2], end + 4, bold)
+            }),
+            '*' => rest[1..]
+                .find('*')
+                .filter(|end| *end > 0 && !rest[1..].starts_with(' '))
+                .map(|end| {
+                    let italic = style.modifier(style.modifier | Modifier::ITALIC);
+                    (&rest[1..=end], end + 2, italic)
+                }),
+            _ => None,
+        };
+        match marked {
+            Some((marked, length, marked_style)) if !marked.is_empty() => {
+                if !plain.is_empty() {
+                    spans.push(Text::styled(std::mem::take(&mut plain), style));
+                }
+                spans.push(Text::styled(marked.to_string(), marked_style));
+                rest = &rest[length..];
+            }
+            _ => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    if !plain.is_empty() {
+        spans.push(Text::styled(plain, style));
+    }
+}
+
+/// Renders the basic markdown of a note: headings, bulleted and numbered
+/// lists, checkboxes, quotes, fenced code blocks and inline code, bold and
+/// emphasis. Anything else is shown as it is.
+pub fn render(text: &str, theme: &Theme) -> Vec<Text<'static>> {
+    let mut spans = vec![];
+    let mut in_code = false;
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let indent = &line[..line.len() - trimmed.len()];
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            spans.push(Text::styled(format!("{}\n", trimmed), theme.muted));
+            continue;
+        }
+        if in_code {
+            spans.push(Text::styled(format!("  {}\n", line), theme.code));
+            continue;
+        }
+        let default = Style::default();
+        if let Some((level, title)) = heading(trimmed) {
+            inline(title, heading_style(level, theme), theme, &mut spans);
+        } else if let Some((checked, item)) = checkbox(trimmed) {
+            let (mark, mark_style, style) = match checked {
+                true => ("[x] ", theme.success, theme.done),
+                false => ("[ ] ", theme.marker, default),
+            };
+            spans.push(Text::styled(format!("{}{}", indent, mark), mark_style));
+            inline(item, style, theme, &mut spans);
+        } else if let Some(item) = bullet(trimmed) {
+            spans.push(Text::styled(format!("{}• ", indent), theme.marker));
+            inline(item, default, theme, &mut spans);
+        } else if let Some((number, item)) = numbered(trimmed) {
+            spans.push(Text::styled(format!("{}{} ", indent, number), theme.marker));
+            inline(item, default, theme, &mut spans);
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            spans.push(Text::styled(format!("{}│ ", indent), theme.quote));
+            inline(quote.trim_start(), theme.quote, theme, &mut spans);
+        } else {
+            spans.push(Text::raw(indent.to_string()));
+            inline(trimmed, default, theme, &mut spans);
+        }
+        spans.push(Text::raw("\n"));
+    }
+    spans
+}
@@ -0,0 +1,116 @@
+use std::cmp::Reverse;
+
+/// How well a candidate matched a pattern and which of its characters (by
+/// char index) matched.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Match {
+    pub score: i64,
+    pub positions: Vec<usize>,
+}
+
+/// Lowercases a single character, so that indices into the lowercased text
+/// are also indices into the original one.
+fn lowercase(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Decides whether a candidate matches what was typed and how well.
+pub trait Matcher {
+    fn matches(&self, pattern: &str, candidate: &str) -> Option<Match>;
+}
+
+/// Case-insensitive substring matching, earlier matches rank higher.
+pub struct SubstringMatcher;
+
+impl Matcher for SubstringMatcher {
+    fn matches(&self, pattern: &str, candidate: &str) -> Option<Match> {
+        let pattern: Vec<char> = pattern.chars().map(lowercase).collect();
+        let candidate: Vec<char> = candidate.chars().map(lowercase).collect();
+        if pattern.is_empty() {
+            return Some(Match::default());
+        }
+        let start = candidate
+            .windows(pattern.len())
+            .position(|window| window == pattern.as_slice())?;
+        Some(Match {
+            score: -(start as i64),
+            positions: (start..start + pattern.len()).collect(),
+        })
+    }
+}
+
+const MATCH_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 12;
+const WORD_START_BONUS: i64 = 8;
+const FIRST_CHAR_BONUS: i64 = 8;
+const GAP_PENALTY: i64 = 1;
+
+/// Case-insensitive subsequence matching: "api" matches "Billing-API" and
+/// "bapi" matches it too. Consecutive characters and characters at the start
+/// of words score higher, gaps cost a little.
+pub struct FuzzyMatcher;
+
+impl FuzzyMatcher {
+    fn is_word_start(candidate: &[char], i: usize) -> bool {
+        match i.checked_sub(1).map(|p| candidate[p]) {
+            None => true,
+            Some(previous) => {
+                !previous.is_alphanumeric()
+                    || (previous.is_lowercase() && candidate[i].is_uppercase())
+            }
+        }
+    }
+
+    /// Matches `pattern` greedily starting with the candidate character at
+    /// `start`.
+    fn match_from(
+        pattern: &[char],
+        candidate: &[char],
+        lower: &[char],
+        start: usize,
+    ) -> Option<Match> {
+        let mut positions = Vec::with_capacity(pattern.len());
+        let mut score = 0;
+        let mut i = start;
+        for c in pattern {
+            let found = (i..lower.len()).find(|j| lower[*j] == *c)?;
+            score += MATCH_SCORE;
+            match positions.last() {
+                Some(last) if found == last + 1 => score += CONSECUTIVE_BONUS,
+                Some(last) => score -= GAP_PENALTY * (found - last - 1) as i64,
+                None => score -= GAP_PENALTY * found as i64,
+            }
+            if FuzzyMatcher::is_word_start(candidate, found) {
+                score += WORD_START_BONUS;
+            }
+            if found == 0 {
+                score += FIRST_CHAR_BONUS;
+            }
+            positions.push(found);
+            i = found + 1;
+        }
+        Some(Match { score, positions })
+    }
+}
+
+impl Matcher for FuzzyMatcher {
+    fn matches(&self, pattern: &str, candidate: &str) -> Option<Match> {
+        let pattern: Vec<char> = pattern
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .map(lowercase)
+            .collect();
+        if pattern.is_empty() {
+            return Some(Match::default());
+        }
+        let candidate: Vec<char> = candidate.chars().collect();
+        // Lowercasing char by char keeps the indices of both in line.
+        let lower: Vec<char> = candidate.iter().cloned().map(lowercase).collect();
+        // Try every place the first character occurs, the greedy match from
+        // the first one isn't necessarily the best.
+        (0..lower.len())
+            .filter(|i| lower[*i] == pattern[0])
+            .filter_map(|i| FuzzyMatcher::match_from(&pattern, &candidate, &lower, i))
+            .max_by_key(|m| (m.score, Reverse(m.positions[0])))
+    }
+}
@@ -6,4 +6,18 @@ pub use app::CodePalAction;
 pub use event::ActionPayload;
 pub use event::Event;
 pub mod event;
+pub mod external;
+pub mod help;
+pub mod history;
+pub mod keymap;
+pub mod layout;
+pub mod markdown;
+pub mod matcher;
+pub mod modal;
+pub mod search;
+pub mod text_buffer;
+pub mod text_pane;
+pub mod theme;
+pub mod todo_view;
 pub mod ui_component;
+pub mod viewport;
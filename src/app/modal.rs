@@ -0,0 +1,150 @@
+use crate::app::keymap::Action;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Normal,
+    Insert,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Normal => "NORMAL",
+            Mode::Insert => "INSERT",
+        }
+    }
+}
+
+/// What a key pressed in normal mode does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalKey {
+    Action(Action),
+    /// Hands a key to the selected row as if it was pressed while typing,
+    /// e.g. to move the cursor.
+    Key(KeyEvent),
+    /// Switches to insert mode, after moving the cursor with the key.
+    Insert(Option<KeyCode>),
+    /// Adds a row with the action and switches to insert mode in it.
+    Open(Action),
+    /// The first key of `gg` or `dd`.
+    Pending,
+    Ignored,
+}
+
+const fn key(code: KeyCode, modifiers: KeyModifiers) -> NormalKey {
+    NormalKey::Key(KeyEvent { code, modifiers })
+}
+
+const PLAIN: KeyModifiers = KeyModifiers::empty();
+
+/// The keys of normal mode, what they do and how the help describes them.
+pub const NORMAL_KEYS: [(&str, NormalKey, &str); 21] = [
+    ("j", NormalKey::Action(Action::Next), "move down"),
+    ("k", NormalKey::Action(Action::Up), "move up"),
+    (
+        "gg",
+        NormalKey::Action(Action::First),
+        "go to the first row",
+    ),
+    ("G", NormalKey::Action(Action::Last), "go to the last row"),
+    ("dd", NormalKey::Action(Action::DeleteRow), "delete the row"),
+    ("o", NormalKey::Open(Action::InsertRow), "open a row below"),
+    (
+        "O",
+        NormalKey::Open(Action::InsertRowAbove),
+        "open a row above",
+    ),
+    ("i", NormalKey::Insert(None), "insert before the cursor"),
+    (
+        "a",
+        NormalKey::Insert(Some(KeyCode::Right)),
+        "insert after the cursor",
+    ),
+    (
+        "I",
+        NormalKey::Insert(Some(KeyCode::Home)),
+        "insert at the start",
+    ),
+    (
+        "A",
+        NormalKey::Insert(Some(KeyCode::End)),
+        "insert at the end",
+    ),
+    ("h", key(KeyCode::Left, PLAIN), "cursor left"),
+    ("l", key(KeyCode::Right, PLAIN), "cursor right"),
+    ("w", key(KeyCode::Right, KeyModifiers::CONTROL), "next word"),
+    (
+        "b",
+        key(KeyCode::Left, KeyModifiers::CONTROL),
+        "previous word",
+    ),
+    ("0", key(KeyCode::Home, PLAIN), "start of the row"),
+    ("$", key(KeyCode::End, PLAIN), "end of the row"),
+    ("x", key(KeyCode::Delete, PLAIN), "delete the character"),
+    ("u", NormalKey::Action(Action::Undo), "undo"),
+    ("ctrl+r", NormalKey::Action(Action::Redo), "redo"),
+    ("/", NormalKey::Action(Action::Search), "search"),
+];
+
+fn normal_key(keys: &str) -> Option<NormalKey> {
+    NORMAL_KEYS
+        .iter()
+        .find(|(k, _, _)| *k == keys)
+        .map(|(_, key, _)| *key)
+}
+
+/// Vim-like editing of the todo list and the notes: in normal mode keys move
+/// between and change rows, only insert mode types into them.
+#[derive(Debug, Clone)]
+pub struct Modal {
+    pub mode: Mode,
+    pending: Option<char>,
+}
+
+impl Default for Modal {
+    fn default() -> Modal {
+        Modal {
+            mode: Mode::Normal,
+            pending: None,
+        }
+    }
+}
+
+impl Modal {
+    /// The first key of a command that waits for a second one.
+    pub fn pending(&self) -> Option<char> {
+        self.pending
+    }
+
+    pub fn set_mode(&mut self, mode: Mode) {
+        self.mode = mode;
+        self.pending = None;
+    }
+
+    /// What `c` does in normal mode, given the keys before it.
+    pub fn on_normal_key(&mut self, c: char) -> NormalKey {
+        let keys = match self.pending.take() {
+            Some(pending) => format!("{}{}", pending, c),
+            None => c.to_string(),
+        };
+        if let Some(key) = normal_key(&keys) {
+            return key;
+        }
+        let starts_sequence = |k: &str| k.chars().count() == 2 && k.starts_with(c);
+        if NORMAL_KEYS.iter().any(|(k, _, _)| starts_sequence(k)) {
+            self.pending = Some(c);
+            return NormalKey::Pending;
+        }
+        match keys.len() {
+            1 => NormalKey::Ignored,
+            _ => self.on_normal_key(c),
+        }
+    }
+
+    /// What ctrl+`c` does in normal mode.
+    pub fn on_normal_ctrl_key(&mut self, c: char) -> NormalKey {
+        self.pending = None;
+        normal_key(&format!("ctrl+{}", c)).unwrap_or(NormalKey::Ignored)
+    }
+}
@@ -0,0 +1,262 @@
+use crate::app::keymap::Action;
+use crate::app::text_buffer::{KeyResult, TextBuffer, TextInput};
+use crate::app::theme::Theme;
+use crate::app::ui_component::{Clear, UIComponent, UIEventProcessor};
+use crate::app::{ActionPayload, Event};
+use crate::todo::todo::{Project, TodoItem};
+use crossterm::event::{KeyCode, KeyEvent};
+use std::sync::mpsc::Sender;
+use tui::backend::Backend;
+use tui::layout::{Constraint, Layout, Rect};
+use tui::style::Style;
+use tui::widgets::{Block, Borders, Paragraph, SelectableList, Widget};
+use tui::Frame;
+
+const SNIPPET_CONTEXT: usize = 24;
+const MAX_RESULTS: usize = 100;
+
+/// Where a search result lives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchTarget {
+    Todo(usize),
+    Note(usize, usize),
+    /// A project and the column that matched.
+    Project(usize, usize),
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchResult {
+    pub target: SearchTarget,
+    pub score: usize,
+    pub label: String,
+}
+
+/// Scores `text` against lowercase `terms`, all of which have to occur in it.
+/// Matches at the start of a word and at the start of the text rank higher.
+fn score(text: &str, terms: &[String]) -> Option<(usize, usize)> {
+    let lower = text.to_lowercase();
+    let mut total = 0;
+    let mut first = None;
+    for term in terms {
+        let position = lower.find(term.as_str())?;
+        let word_start = lower[..position]
+            .chars()
+            .last()
+            .is_none_or(|c| !c.is_alphanumeric());
+        total += if position == 0 {
+            4
+        } else if word_start {
+            3
+        } else {
+            1
+        };
+        first = Some(first.map_or(position, |first: usize| first.min(position)));
+    }
+    first.map(|first| (total, first))
+}
+
+/// The part of `text` around byte `position`, on one line.
+fn snippet(text: &str, position: usize) -> String {
+    let position = if text.is_char_boundary(position) {
+        position
+    } else {
+        0
+    };
+    let before: Vec<char> = text[..position]
+        .chars()
+        .rev()
+        .take(SNIPPET_CONTEXT)
+        .collect();
+    let start = position - before.iter().map(|c| c.len_utf8()).sum::<usize>();
+    let end = text[position..]
+        .char_indices()
+        .nth(SNIPPET_CONTEXT * 2)
+        .map_or(text.len(), |(i, _)| position + i);
+    let mut snippet = text[start..end].replace('\n', " ");
+    if start > 0 {
+        snippet.insert_str(0, "...");
+    }
+    if end < text.len() {
+        snippet.push_str("...");
+    }
+    snippet
+}
+
+/// Searches todo titles, note contents and project fields, best matches
+/// first. Titles weigh more than project fields, which weigh more than notes.
+pub fn search(query: &str, todos: &[TodoItem], projects: &[Project]) -> Vec<SearchResult> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return vec![];
+    }
+    let mut results = vec![];
+    for (i, todo) in todos.iter().enumerate() {
+        if let Some((score, position)) = score(&todo.title, &terms) {
+            results.push(SearchResult {
+                target: SearchTarget::Todo(i),
+                score: score * 3,
+                label: format!("Todo: {}", snippet(&todo.title, position)),
+            });
+        }
+        for (j, note) in todo.notes.borrow().iter().enumerate() {
+            if let Some((score, position)) = score(&note.content, &terms) {
+                results.push(SearchResult {
+                    target: SearchTarget::Note(i, j),
+                    score,
+                    label: format!(
+                        "Note in {}: {}",
+                        todo.title,
+                        snippet(&note.content, position)
+                    ),
+                });
+            }
+        }
+    }
+    for (i, project) in projects.iter().enumerate() {
+        let fields = [&project.name, &project.url, &project.dir_location];
+        let best = fields
+            .iter()
+            .enumerate()
+            .filter_map(|(column, field)| score(field, &terms).map(|(s, p)| (s, p, column)))
+            .max_by_key(|(score, _, column)| (*score, std::cmp::Reverse(*column)));
+        if let Some((score, position, column)) = best {
+            results.push(SearchResult {
+                target: SearchTarget::Project(i, column),
+                score: score * 2,
+                label: format!("Project: {}", snippet(fields[column], position)),
+            });
+        }
+    }
+    // Stable, so equally good matches keep the order of the store.
+    results.sort_by_key(|result| std::cmp::Reverse(result.score));
+    results.truncate(MAX_RESULTS);
+    results
+}
+
+/// The search overlay: a query line and the results for it. Every change to
+/// the query is broadcast as `ActionPayload::Text`, enter broadcasts the
+/// chosen result as a `Selection`.
+pub struct SearchEditor {
+    pub active: bool,
+    pub title: String,
+    pub query: String,
+    pub buffer: TextBuffer,
+    pub results: Vec<SearchResult>,
+    pub current_selection: Option<usize>,
+    pub sender: Sender<Event>,
+}
+
+impl SearchEditor {
+    pub fn new(title: String, sender: Sender<Event>) -> SearchEditor {
+        SearchEditor {
+            active: false,
+            title,
+            query: String::new(),
+            buffer: TextBuffer::new(),
+            results: vec![],
+            current_selection: None,
+            sender,
+        }
+    }
+
+    pub fn set_results(&mut self, results: Vec<SearchResult>) {
+        self.current_selection = if results.is_empty() { None } else { Some(0) };
+        self.results = results;
+    }
+
+    pub fn on_text_event(&mut self, input: impl Into<TextInput>) {
+        self.buffer.attach((0, 0), &self.query);
+        if let KeyResult::Edited(edit, _) = self.buffer.on_input(&self.query, input) {
+            edit.apply(&mut self.query);
+            self.broadcast_text();
+        }
+    }
+
+    pub fn on_up(&mut self) {
+        if let Some(x) = self.current_selection {
+            self.current_selection = Some(x.saturating_sub(1));
+        }
+    }
+
+    pub fn on_down(&mut self) {
+        if let Some(x) = self.current_selection {
+            if x + 1 < self.results.len() {
+                self.current_selection = Some(x + 1);
+            }
+        }
+    }
+
+    pub fn broadcast_text(&mut self) {
+        self.sender
+            .send(Event::Action(ActionPayload::Text(self.query.clone())))
+            .unwrap_or_default();
+    }
+
+    pub fn on_enter(&mut self) {
+        if self.current_selection.is_some() {
+            self.sender
+                .send(Event::Action(ActionPayload::Selection(
+                    self.title.clone(),
+                    self.current_selection,
+                )))
+                .unwrap_or_default();
+        }
+    }
+}
+
+impl UIComponent for SearchEditor {
+    fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
+    where
+        B: Backend,
+    {
+        Clear.render(f, area);
+        Block::default()
+            .borders(Borders::ALL)
+            .title(&self.title)
+            .title_style(theme.title)
+            .render(f, area);
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+            .margin(1)
+            .split(area);
+        self.buffer.attach((0, 0), &self.query);
+        let text = self.buffer.spans(&self.query, Style::default());
+        Paragraph::new(text.iter())
+            .block(Block::default().borders(Borders::ALL).title("Find ..."))
+            .render(f, chunks[0]);
+
+        let items: Vec<_> = self.results.iter().map(|result| &result.label).collect();
+        let title = format!("{} results", self.results.len());
+        SelectableList::default()
+            .block(Block::default().borders(Borders::ALL).title(&title))
+            .items(&items)
+            .select(self.current_selection)
+            .highlight_style(theme.selection)
+            .highlight_symbol(">")
+            .render(f, chunks[1]);
+    }
+}
+
+impl UIEventProcessor for SearchEditor {
+    fn on_deactivate(&mut self) {
+        self.active = false
+    }
+    fn on_activate(&mut self) {
+        self.active = true;
+    }
+    fn on_event(&mut self, event: KeyEvent) {
+        if self.active {
+            match event.code {
+                KeyCode::Up => self.on_up(),
+                KeyCode::Down => self.on_down(),
+                KeyCode::Enter => self.on_enter(),
+                _ => self.on_text_event(event),
+            }
+        }
+    }
+    fn on_action(&mut self, action: Action) {
+        if self.active && action.edits_text() {
+            self.on_text_event(action);
+        }
+    }
+}
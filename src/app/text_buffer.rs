@@ -0,0 +1,542 @@
+use crate::app::history::EditKind;
+use crate::app::keymap::Action;
+use clipboard::{ClipboardContext, ClipboardProvider};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::ops::Range;
+use tui::style::{Modifier, Style};
+use tui::widgets::Text;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A change to a piece of text: `removed` replaced by `inserted` at byte
+/// `position`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edit {
+    pub position: usize,
+    pub removed: String,
+    pub inserted: String,
+}
+
+impl Edit {
+    pub fn apply(&self, text: &mut String) {
+        text.replace_range(
+            self.position..self.position + self.removed.len(),
+            &self.inserted,
+        );
+    }
+}
+
+/// What a key did to the text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyResult {
+    /// The text has to change as described.
+    Edited(Edit, EditKind),
+    /// Only the cursor or selection changed, if anything.
+    Handled,
+    /// Not a text editing key, the editor may do something else with it.
+    Ignored,
+}
+
+/// What editing text reacts to: a key, or an action of the text context.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextInput {
+    Key(KeyEvent),
+    Action(Action),
+}
+
+impl From<KeyEvent> for TextInput {
+    fn from(event: KeyEvent) -> TextInput {
+        TextInput::Key(event)
+    }
+}
+
+impl From<Action> for TextInput {
+    fn from(action: Action) -> TextInput {
+        TextInput::Action(action)
+    }
+}
+
+fn edited(edit: Option<Edit>, kind: EditKind) -> KeyResult {
+    match edit {
+        Some(edit) => KeyResult::Edited(edit, kind),
+        None => KeyResult::Handled,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punctuation,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punctuation
+    }
+}
+
+/// The cursor and selection within a piece of text. The text itself lives
+/// wherever the editor keeps it, so every method is handed the current text;
+/// the ones that change it return the `Edit` to apply and move the cursor as
+/// if it had been applied.
+///
+/// Positions are byte offsets that always sit on grapheme boundaries.
+/// Movement, deletion and the column kept when moving between lines all work
+/// on graphemes, so "é" written as "e" and a combining accent is one step.
+#[derive(Debug, Clone, Default)]
+pub struct TextBuffer {
+    /// What the cursor belongs to, e.g. a row and column of a table.
+    target: Option<(usize, usize)>,
+    cursor: usize,
+    /// Where the selection started, the cursor is its other end.
+    anchor: Option<usize>,
+}
+
+impl TextBuffer {
+    pub fn new() -> TextBuffer {
+        TextBuffer::default()
+    }
+
+    /// Moves the cursor to `target`, placing it at the end of `text` if it
+    /// belonged to something else. Otherwise it's just kept within `text`,
+    /// which may have changed underneath, e.g. by undo.
+    pub fn attach(&mut self, target: (usize, usize), text: &str) {
+        if self.target != Some(target) {
+            self.target = Some(target);
+            self.cursor = text.len();
+            self.anchor = None;
+        } else {
+            self.cursor = TextBuffer::clamp(text, self.cursor);
+            self.anchor = self.anchor.map(|anchor| TextBuffer::clamp(text, anchor));
+        }
+    }
+
+    /// Forgets the target, the next `attach` starts at the end of the text.
+    pub fn detach(&mut self) {
+        self.target = None;
+    }
+
+    fn clamp(text: &str, position: usize) -> usize {
+        if position >= text.len() {
+            return text.len();
+        }
+        text.grapheme_indices(true)
+            .map(|(i, _)| i)
+            .take_while(|i| *i <= position)
+            .last()
+            .unwrap_or(0)
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn selection(&self) -> Option<Range<usize>> {
+        match self.anchor {
+            Some(anchor) if anchor < self.cursor => Some(anchor..self.cursor),
+            Some(anchor) if anchor > self.cursor => Some(self.cursor..anchor),
+            _ => None,
+        }
+    }
+
+    pub fn selected_text<'a>(&self, text: &'a str) -> Option<&'a str> {
+        self.selection().map(|range| &text[range])
+    }
+
+    /// Moves the cursor to `position`, which has to be a grapheme boundary,
+    /// extending the selection if `select`.
+    pub fn move_to(&mut self, position: usize, select: bool) {
+        if select {
+            self.anchor = self.anchor.or(Some(self.cursor));
+        } else {
+            self.anchor = None;
+        }
+        self.cursor = position;
+    }
+
+    pub fn move_start_of_text(&mut self) {
+        self.move_to(0, false);
+    }
+
+    pub fn move_end_of_text(&mut self, text: &str) {
+        self.move_to(text.len(), false);
+    }
+
+    fn previous_grapheme(text: &str, position: usize) -> usize {
+        text[..position]
+            .grapheme_indices(true)
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    fn next_grapheme(text: &str, position: usize) -> usize {
+        text[position..]
+            .graphemes(true)
+            .next()
+            .map_or(position, |g| position + g.len())
+    }
+
+    fn previous_word(text: &str, position: usize) -> usize {
+        let mut chars = text[..position].char_indices().rev().peekable();
+        while chars
+            .peek()
+            .is_some_and(|(_, c)| class(*c) == CharClass::Space)
+        {
+            chars.next();
+        }
+        let word = match chars.peek() {
+            Some((_, c)) => class(*c),
+            None => return 0,
+        };
+        let mut start = position;
+        while let Some((i, c)) = chars.peek() {
+            if class(*c) != word {
+                break;
+            }
+            start = *i;
+            chars.next();
+        }
+        start
+    }
+
+    fn next_word(text: &str, position: usize) -> usize {
+        let mut chars = text[position..].char_indices().peekable();
+        let mut end = position;
+        if let Some((_, first)) = chars.peek().cloned() {
+            let word = class(first);
+            while let Some((i, c)) = chars.peek() {
+                if word == CharClass::Space || class(*c) != word {
+                    break;
+                }
+                end = position + i + c.len_utf8();
+                chars.next();
+            }
+        }
+        while let Some((i, c)) = chars.peek() {
+            if class(*c) != CharClass::Space {
+                break;
+            }
+            end = position + i + c.len_utf8();
+            chars.next();
+        }
+        end
+    }
+
+    fn line_start(text: &str, position: usize) -> usize {
+        text[..position].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    fn line_end(text: &str, position: usize) -> usize {
+        text[position..]
+            .find('\n')
+            .map_or(text.len(), |i| position + i)
+    }
+
+    pub fn left(&mut self, text: &str, select: bool) {
+        // Without shift, left collapses a selection onto its start.
+        let position = match self.selection() {
+            Some(range) if !select => range.start,
+            _ => TextBuffer::previous_grapheme(text, self.cursor),
+        };
+        self.move_to(position, select);
+    }
+
+    pub fn right(&mut self, text: &str, select: bool) {
+        let position = match self.selection() {
+            Some(range) if !select => range.end,
+            _ => TextBuffer::next_grapheme(text, self.cursor),
+        };
+        self.move_to(position, select);
+    }
+
+    pub fn word_left(&mut self, text: &str, select: bool) {
+        self.move_to(TextBuffer::previous_word(text, self.cursor), select);
+    }
+
+    pub fn word_right(&mut self, text: &str, select: bool) {
+        self.move_to(TextBuffer::next_word(text, self.cursor), select);
+    }
+
+    pub fn home(&mut self, text: &str, select: bool) {
+        self.move_to(TextBuffer::line_start(text, self.cursor), select);
+    }
+
+    pub fn end(&mut self, text: &str, select: bool) {
+        self.move_to(TextBuffer::line_end(text, self.cursor), select);
+    }
+
+    pub fn at_start(&self) -> bool {
+        self.cursor == 0
+    }
+
+    pub fn at_end(&self, text: &str) -> bool {
+        self.cursor == text.len()
+    }
+
+    /// The grapheme column of the cursor within its line.
+    pub fn column(&self, text: &str) -> usize {
+        let start = TextBuffer::line_start(text, self.cursor);
+        text[start..self.cursor].graphemes(true).count()
+    }
+
+    /// The position at grapheme `column` of the line starting at `start`, or
+    /// the end of that line if it is shorter.
+    fn at_column(text: &str, start: usize, column: usize) -> usize {
+        let end = TextBuffer::line_end(text, start);
+        text[start..end]
+            .grapheme_indices(true)
+            .nth(column)
+            .map_or(end, |(i, _)| start + i)
+    }
+
+    /// Moves to the same column of the previous line, returns false on the
+    /// first line.
+    pub fn up(&mut self, text: &str, select: bool) -> bool {
+        let start = TextBuffer::line_start(text, self.cursor);
+        if start == 0 {
+            return false;
+        }
+        let column = self.column(text);
+        let previous = TextBuffer::line_start(text, start - 1);
+        self.move_to(TextBuffer::at_column(text, previous, column), select);
+        true
+    }
+
+    /// Moves to the same column of the next line, returns false on the last
+    /// line.
+    pub fn down(&mut self, text: &str, select: bool) -> bool {
+        let end = TextBuffer::line_end(text, self.cursor);
+        if end == text.len() {
+            return false;
+        }
+        let column = self.column(text);
+        self.move_to(TextBuffer::at_column(text, end + 1, column), select);
+        true
+    }
+
+    /// Replaces `range` with `inserted` and puts the cursor after it.
+    fn replace(&mut self, text: &str, range: Range<usize>, inserted: &str) -> Option<Edit> {
+        if range.is_empty() && inserted.is_empty() {
+            return None;
+        }
+        self.anchor = None;
+        self.cursor = range.start + inserted.len();
+        Some(Edit {
+            position: range.start,
+            removed: text[range].to_string(),
+            inserted: inserted.to_string(),
+        })
+    }
+
+    /// Inserts `inserted` at the cursor, replacing the selection if there is
+    /// one.
+    pub fn insert(&mut self, text: &str, inserted: &str) -> Option<Edit> {
+        let range = self.selection().unwrap_or(self.cursor..self.cursor);
+        self.replace(text, range, inserted)
+    }
+
+    /// Deletes the selection, or the grapheme before the cursor.
+    pub fn delete_backward(&mut self, text: &str) -> Option<Edit> {
+        let range = self
+            .selection()
+            .unwrap_or(TextBuffer::previous_grapheme(text, self.cursor)..self.cursor);
+        self.replace(text, range, "")
+    }
+
+    /// Deletes the selection, or the grapheme after the cursor.
+    pub fn delete_forward(&mut self, text: &str) -> Option<Edit> {
+        let range = self
+            .selection()
+            .unwrap_or(self.cursor..TextBuffer::next_grapheme(text, self.cursor));
+        self.replace(text, range, "")
+    }
+
+    /// Deletes the selection, or the word before the cursor.
+    pub fn delete_word_backward(&mut self, text: &str) -> Option<Edit> {
+        let range = self
+            .selection()
+            .unwrap_or(TextBuffer::previous_word(text, self.cursor)..self.cursor);
+        self.replace(text, range, "")
+    }
+
+    /// Deletes the selection, or the word after the cursor.
+    pub fn delete_word_forward(&mut self, text: &str) -> Option<Edit> {
+        let range = self
+            .selection()
+            .unwrap_or(self.cursor..TextBuffer::next_word(text, self.cursor));
+        self.replace(text, range, "")
+    }
+
+    /// Deleting a selection is not part of a run of single deletions.
+    fn deleting(&self) -> EditKind {
+        match self.selection() {
+            Some(_) => EditKind::Other,
+            None => EditKind::Deleting,
+        }
+    }
+
+    /// Edits `text` or moves within it according to `input`.
+    pub fn on_input(&mut self, text: &str, input: impl Into<TextInput>) -> KeyResult {
+        match input.into() {
+            TextInput::Key(event) => self.on_event(text, event),
+            TextInput::Action(action) => self.on_action(text, action),
+        }
+    }
+
+    /// Edits `text` or moves within it according to `event`: characters,
+    /// backspace and delete, alt/ctrl+backspace for the previous word,
+    /// ctrl+delete for the next one, left and right (by word with ctrl or
+    /// alt), home and end, with shift to select. The chords for the
+    /// clipboard are bound in the keymap and arrive through `on_action`.
+    pub fn on_event(&mut self, text: &str, event: KeyEvent) -> KeyResult {
+        let select = event.modifiers.contains(KeyModifiers::SHIFT);
+        let by_word = event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        let deleting = self.deleting();
+        match (event.code, event.modifiers) {
+            (KeyCode::Backspace, _) if by_word => {
+                edited(self.delete_word_backward(text), EditKind::Other)
+            }
+            (KeyCode::Delete, _) if by_word => {
+                edited(self.delete_word_forward(text), EditKind::Other)
+            }
+            (KeyCode::Char(c), _) if !by_word => {
+                let kind = match self.selection() {
+                    Some(_) => EditKind::Other,
+                    None => EditKind::Typing,
+                };
+                edited(self.insert(text, &c.to_string()), kind)
+            }
+            (KeyCode::Backspace, _) => edited(self.delete_backward(text), deleting),
+            (KeyCode::Delete, _) => edited(self.delete_forward(text), deleting),
+            (KeyCode::Left, _) if by_word => {
+                self.word_left(text, select);
+                KeyResult::Handled
+            }
+            (KeyCode::Right, _) if by_word => {
+                self.word_right(text, select);
+                KeyResult::Handled
+            }
+            (KeyCode::Left, _) => {
+                self.left(text, select);
+                KeyResult::Handled
+            }
+            (KeyCode::Right, _) => {
+                self.right(text, select);
+                KeyResult::Handled
+            }
+            (KeyCode::Home, _) => {
+                self.home(text, select);
+                KeyResult::Handled
+            }
+            (KeyCode::End, _) => {
+                self.end(text, select);
+                KeyResult::Handled
+            }
+            _ => KeyResult::Ignored,
+        }
+    }
+
+    /// Performs an action of the text context on `text`.
+    pub fn on_action(&mut self, text: &str, action: Action) -> KeyResult {
+        match action {
+            Action::Copy => {
+                if let Some(selected) = self.selected_text(text) {
+                    set_clipboard(selected);
+                }
+                KeyResult::Handled
+            }
+            Action::Cut => match self.selected_text(text) {
+                Some(selected) => {
+                    set_clipboard(selected);
+                    edited(self.delete_backward(text), EditKind::Other)
+                }
+                None => KeyResult::Handled,
+            },
+            Action::Paste => match get_clipboard() {
+                Some(pasted) => edited(self.insert(text, &pasted), EditKind::Other),
+                None => KeyResult::Handled,
+            },
+            Action::DeleteWord => edited(self.delete_word_backward(text), EditKind::Other),
+            Action::DeleteChar => {
+                let deleting = self.deleting();
+                edited(self.delete_backward(text), deleting)
+            }
+            _ => KeyResult::Ignored,
+        }
+    }
+
+    /// `text` as spans in `style`, with the selection highlighted and the
+    /// cursor shown as a reversed cell.
+    pub fn spans(&self, text: &str, style: Style) -> Vec<Text<'static>> {
+        self.row_spans(text, 0..text.len(), style)
+    }
+
+    /// The part `row` of `text`, which doesn't span a line break, as spans
+    /// like `spans`. A cursor at the end of a line is shown after it, one at
+    /// the end of a wrapped row on the start of the next.
+    pub fn row_spans(&self, text: &str, row: Range<usize>, style: Style) -> Vec<Text<'static>> {
+        let selected = style.modifier(style.modifier | Modifier::REVERSED | Modifier::DIM);
+        let cursor = style.modifier(style.modifier | Modifier::REVERSED);
+        let mut spans = vec![];
+        let mut push = |range: Range<usize>, style: Style| {
+            if !range.is_empty() {
+                spans.push(Text::styled(text[range].to_string(), style));
+            }
+        };
+        let clip = |position: usize| position.max(row.start).min(row.end);
+        match self.selection() {
+            Some(selection) => {
+                let (start, end) = (clip(selection.start), clip(selection.end));
+                push(row.start..start, style);
+                push(start..end, selected);
+                push(end..row.end, style);
+            }
+            None if self.cursor >= row.start && self.cursor < row.end => {
+                let next = TextBuffer::next_grapheme(text, self.cursor).min(row.end);
+                push(row.start..self.cursor, style);
+                push(self.cursor..next, cursor);
+                push(next..row.end, style);
+            }
+            None if self.cursor == row.end
+                && (row.end == text.len() || text[row.end..].starts_with('\n')) =>
+            {
+                push(row.clone(), style);
+                spans.push(Text::styled(" ", cursor));
+            }
+            None => push(row.clone(), style),
+        }
+        spans
+    }
+
+    /// `text` with a marker at the cursor and brackets around the selection,
+    /// for widgets that take plain strings.
+    pub fn marked(&self, text: &str) -> String {
+        match self.selection() {
+            Some(range) => format!(
+                "{}[{}]{}",
+                &text[..range.start],
+                &text[range.clone()],
+                &text[range.end..]
+            ),
+            None => format!("{}|{}", &text[..self.cursor], &text[self.cursor..]),
+        }
+    }
+}
+
+fn get_clipboard() -> Option<String> {
+    let mut ctx: ClipboardContext = ClipboardProvider::new().ok()?;
+    ctx.get_contents().ok()
+}
+
+fn set_clipboard(text: &str) {
+    let ctx: Result<ClipboardContext, _> = ClipboardProvider::new();
+    if let Ok(mut ctx) = ctx {
+        ctx.set_contents(text.to_string()).unwrap_or_default();
+    }
+}
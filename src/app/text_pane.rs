@@ -0,0 +1,397 @@
+use crate::app::history::{Command, EditKind, LinesEdit, TextEdit};
+use crate::app::keymap::Action;
+use crate::app::markdown;
+use crate::app::text_buffer::{Edit, KeyResult, TextBuffer};
+use crate::app::theme::Theme;
+use crate::app::ui_component::{UIComponent, UIEventProcessor};
+use crate::app::viewport::WHEEL_ROWS;
+use crate::todo::todo::{Note, TodoItem};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use std::cell::RefCell;
+use std::ops::Range;
+use std::rc::Rc;
+use tui::backend::Backend;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::{Block, Borders, Paragraph, Text, Widget};
+use tui::Frame;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaneMode {
+    /// The markdown of the text, rendered.
+    Read,
+    Edit,
+}
+
+fn note_content(note: &mut Note, _: usize) -> &mut String {
+    &mut note.content
+}
+
+fn todo_description(todo: &mut TodoItem) -> &mut Vec<String> {
+    &mut todo.description
+}
+
+/// The text a `TextPane` shows.
+#[derive(Clone)]
+pub enum PaneText {
+    /// The note at an index of a todo's notes.
+    Note(Rc<RefCell<Vec<Note>>>, usize),
+    /// The description of the todo at an index, kept as lines.
+    Description(Rc<RefCell<Vec<TodoItem>>>, usize),
+}
+
+impl PaneText {
+    fn content(&self) -> Option<String> {
+        match self {
+            PaneText::Note(notes, index) => {
+                notes.borrow().get(*index).map(|note| note.content.clone())
+            }
+            PaneText::Description(todos, index) => todos
+                .borrow()
+                .get(*index)
+                .map(|todo| todo.description.join("\n")),
+        }
+    }
+
+    fn is(&self, other: &PaneText) -> bool {
+        match (self, other) {
+            (PaneText::Note(a, i), PaneText::Note(b, j)) => Rc::ptr_eq(a, b) && i == j,
+            (PaneText::Description(a, i), PaneText::Description(b, j)) => {
+                Rc::ptr_eq(a, b) && i == j
+            }
+            _ => false,
+        }
+    }
+
+    /// The command making `edit` to the text.
+    fn command(&self, edit: Edit, kind: EditKind) -> Box<dyn Command> {
+        match self {
+            PaneText::Note(notes, index) => Box::new(TextEdit {
+                list: notes.clone(),
+                index: *index,
+                field: 0,
+                accessor: note_content,
+                position: edit.position,
+                removed: edit.removed,
+                inserted: edit.inserted,
+                kind,
+            }),
+            PaneText::Description(todos, index) => Box::new(LinesEdit {
+                list: todos.clone(),
+                index: *index,
+                accessor: todo_description,
+                position: edit.position,
+                removed: edit.removed,
+                inserted: edit.inserted,
+                kind,
+            }),
+        }
+    }
+}
+
+/// Splits `text` into the rows it takes up in `width` columns: every line
+/// on its own, long lines broken after the last space that fits or, if there
+/// is none, wherever they reach the width. The rows don't include the line
+/// breaks.
+pub fn wrap(text: &str, width: usize) -> Vec<Range<usize>> {
+    let width = width.max(1);
+    let mut rows = vec![];
+    let mut line_start = 0;
+    for line in text.split('\n') {
+        let mut start = line_start;
+        let mut used = 0;
+        let mut last_space = None;
+        for (i, g) in line.grapheme_indices(true) {
+            let position = line_start + i;
+            let g_width = g.width();
+            if used + g_width > width && position > start {
+                let end = last_space.filter(|end| *end > start).unwrap_or(position);
+                rows.push(start..end);
+                start = end;
+                used = text[start..position].width();
+                last_space = None;
+            }
+            used += g_width;
+            if g.chars().all(char::is_whitespace) {
+                last_space = Some(position + g.len());
+            }
+        }
+        rows.push(start..line_start + line.len());
+        line_start += line.len() + 1;
+    }
+    rows
+}
+
+/// The row of `rows` the cursor at `position` is shown on.
+fn row_of(rows: &[Range<usize>], position: usize) -> usize {
+    rows.iter()
+        .position(|row| row.start <= position && position < row.end)
+        .or_else(|| rows.iter().position(|row| row.end == position))
+        .unwrap_or(0)
+}
+
+/// A note or description in full: its markdown rendered in read mode, or
+/// its text with soft wrapped lines in edit mode.
+pub struct TextPane {
+    pub title: String,
+    pub text: Option<PaneText>,
+    pub mode: PaneMode,
+    pub buffer: TextBuffer,
+    /// The first row shown.
+    pub scroll: usize,
+    /// The size of the last draw, for moving by rows and pages.
+    width: usize,
+    height: usize,
+    /// Where it was last drawn, for the mouse.
+    pub area: Rect,
+    pub active: bool,
+    pub changes: Vec<Box<dyn Command>>,
+}
+
+impl TextPane {
+    pub fn new(title: String) -> TextPane {
+        TextPane {
+            title,
+            text: None,
+            mode: PaneMode::Read,
+            buffer: TextBuffer::new(),
+            scroll: 0,
+            width: 0,
+            height: 0,
+            area: Rect::default(),
+            active: false,
+            changes: vec![],
+        }
+    }
+
+    /// Shows `text`, starting at its top if it's different from before.
+    pub fn show(&mut self, text: Option<PaneText>) {
+        let same = match (&text, &self.text) {
+            (Some(a), Some(b)) => a.is(b),
+            (None, None) => true,
+            _ => false,
+        };
+        if !same {
+            self.buffer.detach();
+            self.scroll = 0;
+        }
+        self.text = text;
+    }
+
+    fn content(&self) -> Option<String> {
+        self.text.as_ref()?.content()
+    }
+
+    /// Puts the cursor on the text, returns its content.
+    fn attach_cursor(&mut self) -> Option<String> {
+        let text = self.content()?;
+        self.buffer.attach((0, 0), &text);
+        Some(text)
+    }
+
+    fn edit_text(&mut self, edit: Edit, kind: EditKind) {
+        if let Some(text) = self.text.as_ref() {
+            let mut command = text.command(edit, kind);
+            command.apply();
+            self.changes.push(command);
+        }
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            PaneMode::Read => PaneMode::Edit,
+            PaneMode::Edit => PaneMode::Read,
+        };
+        self.scroll = 0;
+    }
+
+    /// Moves the cursor `count` rows down, or up if negative, keeping its
+    /// column.
+    fn move_rows(&mut self, text: &str, count: isize, select: bool) {
+        let rows = match self.width {
+            0 => wrap(text, usize::MAX),
+            width => wrap(text, width),
+        };
+        let current = row_of(&rows, self.buffer.cursor());
+        let row = &rows[current];
+        let column = text[row.start..self.buffer.cursor()].width();
+        let target = (current as isize + count)
+            .max(0)
+            .min(rows.len() as isize - 1) as usize;
+        let target_row = &rows[target];
+        // The last grapheme that starts before the column.
+        let mut position = target_row.start;
+        for (i, g) in text[target_row.clone()].grapheme_indices(true) {
+            if text[target_row.start..target_row.start + i].width() + g.width() > column {
+                break;
+            }
+            position = target_row.start + i + g.len();
+        }
+        self.buffer.move_to(position, select);
+    }
+
+    fn on_edit_event(&mut self, event: KeyEvent) {
+        let text = match self.attach_cursor() {
+            Some(text) => text,
+            None => return,
+        };
+        let select = event.modifiers.contains(KeyModifiers::SHIFT);
+        let page = self.height.max(1) as isize;
+        match event.code {
+            KeyCode::Enter => {
+                if let Some(edit) = self.buffer.insert(&text, "\n") {
+                    self.edit_text(edit, EditKind::Typing);
+                }
+            }
+            KeyCode::Tab => {
+                if let Some(edit) = self.buffer.insert(&text, "  ") {
+                    self.edit_text(edit, EditKind::Typing);
+                }
+            }
+            KeyCode::Up => self.move_rows(&text, -1, select),
+            KeyCode::Down => self.move_rows(&text, 1, select),
+            KeyCode::PageUp => self.move_rows(&text, -page, select),
+            KeyCode::PageDown => self.move_rows(&text, page, select),
+            _ => {
+                if let KeyResult::Edited(edit, kind) = self.buffer.on_event(&text, event) {
+                    self.edit_text(edit, kind);
+                }
+            }
+        }
+    }
+
+    fn on_read_event(&mut self, event: KeyEvent) {
+        let page = self.height.max(1);
+        match event.code {
+            KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down => self.scroll += 1,
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(page),
+            KeyCode::PageDown => self.scroll += page,
+            KeyCode::Home => self.scroll = 0,
+            _ => {}
+        }
+        let lines = self.content().map_or(0, |text| text.lines().count());
+        self.scroll = self.scroll.min(lines.saturating_sub(1));
+    }
+}
+
+impl UIEventProcessor for TextPane {
+    fn on_deactivate(&mut self) {
+        self.active = false;
+        self.mode = PaneMode::Read;
+    }
+    fn on_activate(&mut self) {
+        self.active = true;
+        self.mode = PaneMode::Edit;
+    }
+    fn on_event(&mut self, event: KeyEvent) {
+        if !self.active {
+            return;
+        }
+        match self.mode {
+            PaneMode::Edit => self.on_edit_event(event),
+            PaneMode::Read => self.on_read_event(event),
+        }
+    }
+    /// The wheel moves the cursor while editing, and scrolls otherwise.
+    fn on_mouse(&mut self, event: MouseEvent) {
+        let rows = match event {
+            MouseEvent::ScrollUp(..) => -(WHEEL_ROWS as isize),
+            MouseEvent::ScrollDown(..) => WHEEL_ROWS as isize,
+            _ => return,
+        };
+        match (self.active, self.mode) {
+            (true, PaneMode::Edit) => {
+                if let Some(text) = self.attach_cursor() {
+                    self.move_rows(&text, rows, false);
+                }
+            }
+            _ => {
+                let lines = self.content().map_or(0, |text| text.lines().count());
+                self.scroll = (self.scroll as isize + rows)
+                    .max(0)
+                    .min(lines.saturating_sub(1) as isize) as usize;
+            }
+        }
+    }
+    fn on_action(&mut self, action: Action) {
+        if !self.active {
+            return;
+        }
+        match (action, self.mode) {
+            (Action::TogglePreview, _) => self.toggle_mode(),
+            (action, PaneMode::Edit) if action.edits_text() => {
+                if let Some(text) = self.attach_cursor() {
+                    if let KeyResult::Edited(edit, kind) = self.buffer.on_action(&text, action) {
+                        self.edit_text(edit, kind);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    fn take_changes(&mut self) -> Vec<Box<dyn Command>> {
+        std::mem::take(&mut self.changes)
+    }
+}
+
+impl UIComponent for TextPane {
+    fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
+    where
+        B: Backend,
+    {
+        let title_style = match self.active {
+            true => theme.title,
+            false => Style::default(),
+        };
+        let title = match (self.active, self.mode) {
+            (true, PaneMode::Edit) => format!("{} (editing, ctrl+r to preview)", self.title),
+            (true, PaneMode::Read) => format!("{} (preview, ctrl+r to edit)", self.title),
+            (false, _) => self.title.clone(),
+        };
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(&title)
+            .title_style(title_style);
+        self.area = area;
+        self.width = area.width.saturating_sub(2) as usize;
+        self.height = area.height.saturating_sub(2) as usize;
+        let text = match self.content() {
+            Some(text) => text,
+            None => {
+                Paragraph::new([].iter()).block(block).render(f, area);
+                return;
+            }
+        };
+
+        if let (true, PaneMode::Edit) = (self.active, self.mode) {
+            self.attach_cursor();
+            let rows = wrap(&text, self.width);
+            // Scroll just enough to keep the cursor in view.
+            let cursor_row = row_of(&rows, self.buffer.cursor());
+            if cursor_row < self.scroll {
+                self.scroll = cursor_row;
+            } else if self.height > 0 && cursor_row >= self.scroll + self.height {
+                self.scroll = cursor_row + 1 - self.height;
+            }
+            let mut lines = vec![];
+            for row in rows {
+                lines.extend(self.buffer.row_spans(&text, row, Style::default()));
+                lines.push(Text::raw("\n"));
+            }
+            Paragraph::new(lines.iter())
+                .block(block)
+                .scroll(self.scroll as u16)
+                .render(f, area);
+        } else {
+            let lines = markdown::render(&text, theme);
+            Paragraph::new(lines.iter())
+                .block(block)
+                .wrap(true)
+                .scroll(self.scroll as u16)
+                .render(f, area);
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use crate::config::ThemeConfig;
+use crate::todo::todo::{Note, Priority, Status, TodoItem};
+use chrono::Local;
+use failure::{bail, format_err, Error};
+use tui::style::{Color, Modifier, Style};
+
+/// The names `--theme` and `[theme] name` accept.
+pub const THEME_NAMES: [&str; 5] = ["dark", "light", "solarized", "high-contrast", "monochrome"];
+
+/// The styles everything is drawn with, one per kind of thing shown.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub tabs: Style,
+    pub selected_tab: Style,
+    /// The title of the pane that has the focus.
+    pub title: Style,
+    /// The selected row of a list or table.
+    pub selection: Style,
+    /// Table headers, help sections and markdown headings.
+    pub header: Style,
+    /// The group titles of the todo list.
+    pub group: Style,
+    /// Matched characters of suggestions and keys in the help.
+    pub accent: Style,
+    pub code: Style,
+    /// Bullets, numbers and open checkboxes in markdown.
+    pub marker: Style,
+    /// Checked checkboxes in markdown.
+    pub success: Style,
+    /// Hints and anything that matters less.
+    pub muted: Style,
+    pub warning: Style,
+    pub error: Style,
+    pub in_progress: Style,
+    pub blocked: Style,
+    pub done: Style,
+    pub quote: Style,
+    pub normal_mode: Style,
+    pub insert_mode: Style,
+}
+
+const SOLARIZED_BASE03: Color = Color::Rgb(0, 43, 54);
+const SOLARIZED_BASE01: Color = Color::Rgb(88, 110, 117);
+const SOLARIZED_YELLOW: Color = Color::Rgb(181, 137, 0);
+const SOLARIZED_ORANGE: Color = Color::Rgb(203, 75, 22);
+const SOLARIZED_RED: Color = Color::Rgb(220, 50, 47);
+const SOLARIZED_MAGENTA: Color = Color::Rgb(211, 54, 130);
+const SOLARIZED_VIOLET: Color = Color::Rgb(108, 113, 196);
+const SOLARIZED_BLUE: Color = Color::Rgb(38, 139, 210);
+const SOLARIZED_CYAN: Color = Color::Rgb(42, 161, 152);
+const SOLARIZED_GREEN: Color = Color::Rgb(133, 153, 0);
+
+fn fg(color: Color) -> Style {
+    Style::default().fg(color)
+}
+
+fn bold(color: Color) -> Style {
+    Style::default().fg(color).modifier(Modifier::BOLD)
+}
+
+fn plain(modifier: Modifier) -> Style {
+    Style::default().modifier(modifier)
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub fn dark() -> Theme {
+        Theme {
+            tabs: fg(Color::Green),
+            selected_tab: fg(Color::Yellow),
+            title: fg(Color::Yellow),
+            selection: bold(Color::Yellow),
+            header: bold(Color::Yellow),
+            group: bold(Color::Green),
+            accent: fg(Color::Cyan),
+            code: fg(Color::Cyan),
+            marker: fg(Color::Yellow),
+            success: fg(Color::Green),
+            muted: fg(Color::DarkGray),
+            warning: fg(Color::Yellow),
+            error: fg(Color::Red),
+            in_progress: fg(Color::Cyan),
+            blocked: fg(Color::Magenta),
+            done: fg(Color::DarkGray).modifier(Modifier::CROSSED_OUT),
+            quote: fg(Color::Gray).modifier(Modifier::ITALIC),
+            normal_mode: fg(Color::Black).bg(Color::Blue),
+            insert_mode: fg(Color::Black).bg(Color::Green),
+        }
+    }
+
+    /// For terminals with a light background, where yellow and cyan fade.
+    pub fn light() -> Theme {
+        Theme {
+            tabs: fg(Color::Blue),
+            selected_tab: bold(Color::Magenta),
+            title: fg(Color::Blue),
+            selection: bold(Color::Blue),
+            header: bold(Color::Magenta),
+            group: bold(Color::Green),
+            accent: fg(Color::Magenta),
+            code: fg(Color::Magenta),
+            marker: fg(Color::Blue),
+            success: fg(Color::Green),
+            muted: fg(Color::Gray),
+            warning: fg(Color::Magenta),
+            error: fg(Color::Red),
+            in_progress: fg(Color::Blue),
+            blocked: fg(Color::Magenta),
+            done: fg(Color::Gray).modifier(Modifier::CROSSED_OUT),
+            quote: fg(Color::DarkGray).modifier(Modifier::ITALIC),
+            normal_mode: fg(Color::White).bg(Color::Blue),
+            insert_mode: fg(Color::White).bg(Color::Green),
+        }
+    }
+
+    /// The accent colors of Solarized, which read on its dark and light
+    /// backgrounds alike.
+    pub fn solarized() -> Theme {
+        Theme {
+            tabs: fg(SOLARIZED_GREEN),
+            selected_tab: fg(SOLARIZED_YELLOW),
+            title: fg(SOLARIZED_YELLOW),
+            selection: bold(SOLARIZED_YELLOW),
+            header: bold(SOLARIZED_ORANGE),
+            group: bold(SOLARIZED_GREEN),
+            accent: fg(SOLARIZED_CYAN),
+            code: fg(SOLARIZED_CYAN),
+            marker: fg(SOLARIZED_BLUE),
+            success: fg(SOLARIZED_GREEN),
+            muted: fg(SOLARIZED_BASE01),
+            warning: fg(SOLARIZED_ORANGE),
+            error: fg(SOLARIZED_RED),
+            in_progress: fg(SOLARIZED_BLUE),
+            blocked: fg(SOLARIZED_MAGENTA),
+            done: fg(SOLARIZED_BASE01).modifier(Modifier::CROSSED_OUT),
+            quote: fg(SOLARIZED_VIOLET).modifier(Modifier::ITALIC),
+            normal_mode: fg(SOLARIZED_BASE03).bg(SOLARIZED_BLUE),
+            insert_mode: fg(SOLARIZED_BASE03).bg(SOLARIZED_GREEN),
+        }
+    }
+
+    /// Bright colors and bold text, with the selection and the tab on a
+    /// background of their own.
+    pub fn high_contrast() -> Theme {
+        Theme {
+            tabs: fg(Color::White),
+            selected_tab: bold(Color::Black).bg(Color::Yellow),
+            title: bold(Color::LightYellow),
+            selection: bold(Color::Black).bg(Color::White),
+            header: bold(Color::White).modifier(Modifier::BOLD | Modifier::UNDERLINED),
+            group: bold(Color::LightGreen),
+            accent: bold(Color::LightCyan),
+            code: fg(Color::LightCyan),
+            marker: fg(Color::LightYellow),
+            success: fg(Color::LightGreen),
+            muted: fg(Color::Gray),
+            warning: bold(Color::LightYellow),
+            error: bold(Color::LightRed),
+            in_progress: fg(Color::LightCyan),
+            blocked: fg(Color::LightMagenta),
+            done: fg(Color::Gray).modifier(Modifier::CROSSED_OUT),
+            quote: fg(Color::White).modifier(Modifier::ITALIC),
+            normal_mode: bold(Color::Black).bg(Color::LightBlue),
+            insert_mode: bold(Color::Black).bg(Color::LightGreen),
+        }
+    }
+
+    /// No colors at all, only bold, underlined, dim and reversed text.
+    pub fn monochrome() -> Theme {
+        Theme {
+            tabs: Style::default(),
+            selected_tab: plain(Modifier::REVERSED),
+            title: plain(Modifier::BOLD),
+            selection: plain(Modifier::BOLD | Modifier::UNDERLINED),
+            header: plain(Modifier::BOLD),
+            group: plain(Modifier::BOLD | Modifier::UNDERLINED),
+            accent: plain(Modifier::BOLD),
+            code: plain(Modifier::ITALIC),
+            marker: plain(Modifier::BOLD),
+            success: plain(Modifier::BOLD),
+            muted: plain(Modifier::DIM),
+            warning: plain(Modifier::BOLD),
+            error: plain(Modifier::BOLD | Modifier::UNDERLINED),
+            in_progress: plain(Modifier::ITALIC),
+            blocked: plain(Modifier::UNDERLINED),
+            done: plain(Modifier::DIM | Modifier::CROSSED_OUT),
+            quote: plain(Modifier::ITALIC),
+            normal_mode: plain(Modifier::REVERSED),
+            insert_mode: plain(Modifier::REVERSED | Modifier::BOLD),
+        }
+    }
+
+    pub fn named(name: &str) -> Result<Theme, Error> {
+        Ok(match name {
+            "dark" => Theme::dark(),
+            "light" => Theme::light(),
+            "solarized" => Theme::solarized(),
+            "high-contrast" => Theme::high_contrast(),
+            "monochrome" => Theme::monochrome(),
+            _ => bail!(
+                "unknown theme {:?}, expected {}",
+                name,
+                THEME_NAMES.join(", ")
+            ),
+        })
+    }
+
+    /// The theme named by `--theme`, or else by the config, or else
+    /// monochrome when `NO_COLOR` is set, with the colors of the config on
+    /// top.
+    pub fn from_config(
+        config: &ThemeConfig,
+        flag: Option<&str>,
+        no_color: bool,
+    ) -> Result<Theme, Error> {
+        let name = match (flag, config.name.as_deref()) {
+            (Some(name), _) | (None, Some(name)) => name,
+            (None, None) if no_color => "monochrome",
+            (None, None) => "dark",
+        };
+        let mut theme = Theme::named(name)?;
+        for (element, color) in &config.colors {
+            let style = theme
+                .style_mut(element)
+                .ok_or_else(|| format_err!("unknown element {:?} in theme.colors", element))?;
+            *style = style.fg(parse_color(color)?);
+        }
+        Ok(theme)
+    }
+
+    fn style_mut(&mut self, element: &str) -> Option<&mut Style> {
+        Some(match element {
+            "tabs" => &mut self.tabs,
+            "selected_tab" => &mut self.selected_tab,
+            "title" => &mut self.title,
+            "selection" => &mut self.selection,
+            "header" => &mut self.header,
+            "group" => &mut self.group,
+            "accent" => &mut self.accent,
+            "code" => &mut self.code,
+            "marker" => &mut self.marker,
+            "success" => &mut self.success,
+            "muted" => &mut self.muted,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "in_progress" => &mut self.in_progress,
+            "blocked" => &mut self.blocked,
+            "done" => &mut self.done,
+            "quote" => &mut self.quote,
+            "normal_mode" => &mut self.normal_mode,
+            "insert_mode" => &mut self.insert_mode,
+            _ => return None,
+        })
+    }
+}
+
+/// `style` with the colors `over` sets and the modifiers of both.
+/// Something shown in a list, in a style of its own.
+pub trait Styled {
+    fn style(&self, _: &Theme) -> Style {
+        Style::default()
+    }
+}
+
+impl Styled for Note {}
+
+impl Styled for TodoItem {
+    /// Colored by status, bold when important and dim when not, and in the
+    /// error color when overdue.
+    fn style(&self, theme: &Theme) -> Style {
+        let style = match self.status {
+            Status::Todo => Style::default(),
+            Status::InProgress => theme.in_progress,
+            Status::Blocked => theme.blocked,
+            Status::Done => theme.done,
+        };
+        let style = match self.priority {
+            Priority::High => style.modifier(style.modifier | Modifier::BOLD),
+            Priority::Low => style.modifier(style.modifier | Modifier::DIM),
+            Priority::Normal => style,
+        };
+        if self.is_overdue(Local::today().naive_local()) {
+            patch(style, theme.error)
+        } else {
+            style
+        }
+    }
+}
+
+pub fn patch(style: Style, over: Style) -> Style {
+    Style {
+        fg: match over.fg {
+            Color::Reset => style.fg,
+            fg => fg,
+        },
+        bg: match over.bg {
+            Color::Reset => style.bg,
+            bg => bg,
+        },
+        modifier: style.modifier | over.modifier,
+    }
+}
+
+const COLOR_NAMES: [(&str, Color); 17] = [
+    ("reset", Color::Reset),
+    ("black", Color::Black),
+    ("red", Color::Red),
+    ("green", Color::Green),
+    ("yellow", Color::Yellow),
+    ("blue", Color::Blue),
+    ("magenta", Color::Magenta),
+    ("cyan", Color::Cyan),
+    ("gray", Color::Gray),
+    ("dark_gray", Color::DarkGray),
+    ("light_red", Color::LightRed),
+    ("light_green", Color::LightGreen),
+    ("light_yellow", Color::LightYellow),
+    ("light_blue", Color::LightBlue),
+    ("light_magenta", Color::LightMagenta),
+    ("light_cyan", Color::LightCyan),
+    ("white", Color::White),
+];
+
+/// A color written as a name like `dark_gray`, `#rrggbb` or a number of
+/// the 256 color palette.
+pub fn parse_color(s: &str) -> Result<Color, Error> {
+    let lower = s.to_ascii_lowercase();
+    if let Some((_, color)) = COLOR_NAMES.iter().find(|(name, _)| *name == lower) {
+        return Ok(*color);
+    }
+    if let Some(hex) = lower
+        .strip_prefix('#')
+        .filter(|hex| hex.len() == 6 && hex.is_ascii())
+    {
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+        if let (Ok(r), Ok(g), Ok(b)) = (channel(0), channel(2), channel(4)) {
+            return Ok(Color::Rgb(r, g, b));
+        }
+    }
+    match lower.parse() {
+        Ok(index) => Ok(Color::Indexed(index)),
+        Err(_) => bail!(
+            "unknown color {:?}, expected a name like dark_gray, #rrggbb or 0 to 255",
+            s
+        ),
+    }
+}
@@ -0,0 +1,206 @@
+use crate::app::ui_component::ViewRow;
+use crate::todo::todo::{Project, Status, TodoItem};
+use std::cmp::Reverse;
+
+const NO_PROJECT: &str = "No project";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortMode {
+    Manual,
+    Priority,
+    Due,
+    Created,
+    Updated,
+}
+
+impl SortMode {
+    pub fn next(self) -> SortMode {
+        match self {
+            SortMode::Manual => SortMode::Priority,
+            SortMode::Priority => SortMode::Due,
+            SortMode::Due => SortMode::Created,
+            SortMode::Created => SortMode::Updated,
+            SortMode::Updated => SortMode::Manual,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SortMode::Manual => "manual",
+            SortMode::Priority => "priority",
+            SortMode::Due => "due date",
+            SortMode::Created => "newest",
+            SortMode::Updated => "recently updated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusFilter {
+    Is(Status),
+    /// Anything that isn't done.
+    Open,
+}
+
+/// A parsed filter query. Words are matched against the title, `#tag`
+/// against the tags, `project:name` against the project name and
+/// `status:todo|in_progress|blocked|done|open` against the status. All of
+/// them have to match.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TodoFilter {
+    pub words: Vec<String>,
+    pub tags: Vec<String>,
+    pub project: Option<String>,
+    pub status: Option<StatusFilter>,
+}
+
+impl TodoFilter {
+    pub fn parse(query: &str) -> TodoFilter {
+        let mut filter = TodoFilter::default();
+        for token in query.split_whitespace() {
+            let lower = token.to_lowercase();
+            if let Some(tag) = lower.strip_prefix('#') {
+                filter.tags.push(tag.to_string());
+            } else if let Some(project) = lower.strip_prefix("project:") {
+                filter.project = Some(project.to_string());
+            } else if let Some(status) = lower.strip_prefix("status:") {
+                filter.status = match status {
+                    "todo" => Some(StatusFilter::Is(Status::Todo)),
+                    "in_progress" | "progress" => Some(StatusFilter::Is(Status::InProgress)),
+                    "blocked" => Some(StatusFilter::Is(Status::Blocked)),
+                    "done" => Some(StatusFilter::Is(Status::Done)),
+                    "open" => Some(StatusFilter::Open),
+                    _ => None,
+                };
+            } else {
+                filter.words.push(lower);
+            }
+        }
+        filter
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == TodoFilter::default()
+    }
+
+    pub fn matches(&self, todo: &TodoItem, project_name: &str) -> bool {
+        let title = todo.title.to_lowercase();
+        let tags: Vec<_> = todo.tags().iter().map(|tag| tag.to_lowercase()).collect();
+        let status = match self.status {
+            Some(StatusFilter::Is(status)) => todo.status == status,
+            Some(StatusFilter::Open) => todo.status != Status::Done,
+            None => true,
+        };
+        status
+            && self.words.iter().all(|word| title.contains(word.as_str()))
+            && self.tags.iter().all(|tag| tags.contains(tag))
+            && self
+                .project
+                .as_ref()
+                .is_none_or(|project| project_name.to_lowercase().contains(project.as_str()))
+    }
+}
+
+/// How the todo list is presented: which items are shown, in which order and
+/// whether they're grouped by project. The underlying list is never
+/// reordered, the editor only gets the indices to show.
+#[derive(Debug, Clone)]
+pub struct TodoView {
+    pub query: String,
+    pub filter: TodoFilter,
+    pub sort: SortMode,
+    pub group_by_project: bool,
+}
+
+impl Default for TodoView {
+    fn default() -> TodoView {
+        TodoView {
+            query: String::new(),
+            filter: TodoFilter::default(),
+            sort: SortMode::Manual,
+            group_by_project: false,
+        }
+    }
+}
+
+impl TodoView {
+    pub fn set_query(&mut self, query: &str) {
+        self.query = query.to_string();
+        self.filter = TodoFilter::parse(query);
+    }
+
+    pub fn is_default(&self) -> bool {
+        self.filter.is_empty() && self.sort == SortMode::Manual && !self.group_by_project
+    }
+
+    pub fn describe(&self) -> String {
+        let mut description = format!("sort: {}", self.sort.name());
+        if self.group_by_project {
+            description.push_str(", grouped by project");
+        }
+        description
+    }
+
+    /// The rows to show for `todos`. `keep` is shown even if it doesn't
+    /// match the filter, so that the item being edited doesn't disappear.
+    pub fn rows(
+        &self,
+        todos: &[TodoItem],
+        projects: &[Project],
+        keep: Option<usize>,
+    ) -> Vec<ViewRow> {
+        let project_name = |todo: &TodoItem| {
+            projects
+                .iter()
+                .find(|project| project.identifier == todo.project_identifier)
+                .map(|project| project.name.as_str())
+                .unwrap_or("")
+        };
+        let mut indices: Vec<usize> = (0..todos.len())
+            .filter(|i| {
+                Some(*i) == keep || self.filter.matches(&todos[*i], project_name(&todos[*i]))
+            })
+            .collect();
+        match self.sort {
+            SortMode::Manual => {}
+            SortMode::Priority => indices.sort_by_key(|i| Reverse(todos[*i].priority as u8)),
+            SortMode::Due => indices.sort_by_key(|i| (todos[*i].due.is_none(), todos[*i].due)),
+            SortMode::Created => indices.sort_by_key(|i| Reverse(todos[*i].created_at)),
+            SortMode::Updated => indices.sort_by_key(|i| Reverse(todos[*i].updated_at)),
+        }
+        if !self.group_by_project {
+            return indices.into_iter().map(ViewRow::Item).collect();
+        }
+
+        let mut rows = vec![];
+        let groups = projects
+            .iter()
+            .map(|project| (project.identifier.as_str(), project.name.as_str()))
+            .chain(std::iter::once(("", NO_PROJECT)));
+        for (identifier, name) in groups {
+            let known = |todo: &TodoItem| {
+                projects
+                    .iter()
+                    .any(|project| project.identifier == todo.project_identifier)
+            };
+            let members: Vec<_> = indices
+                .iter()
+                .filter(|i| match identifier {
+                    "" => !known(&todos[**i]),
+                    _ => todos[**i].project_identifier == identifier,
+                })
+                .collect();
+            if members.is_empty() {
+                continue;
+            }
+            let name = if name.is_empty() {
+                "(unnamed project)"
+            } else {
+                name
+            };
+            rows.push(ViewRow::Group(name.to_string()));
+            rows.extend(members.into_iter().map(|i| ViewRow::Item(*i)));
+        }
+        rows
+    }
+}
@@ -1,56 +1,339 @@
+use chrono::Utc;
 use std::io;
 
 use tui::backend::Backend;
 use tui::layout::{Constraint, Direction, Layout, Rect};
-use tui::style::{Color, Style};
-use tui::widgets::{Block, Borders, Tabs, Widget};
+use tui::widgets::{Block, Borders, Paragraph, Tabs, Text, Widget};
 use tui::{Frame, Terminal};
 
-use crate::app::{ui_component::UIComponent, App};
+use crate::app::keymap::{Action, Context};
+use crate::app::layout;
+use crate::app::modal::Mode;
+use crate::app::{ui_component::UIComponent, App, CodePalAction};
 
 pub fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), io::Error> {
+    let title = format!("{} [{}]", app.title, app.workspace);
     terminal.draw(|mut f| {
         let chunks = Layout::default()
-            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .constraints(
+                [
+                    Constraint::Length(3),
+                    Constraint::Min(0),
+                    Constraint::Length(1),
+                ]
+                .as_ref(),
+            )
             .split(f.size());
+        app.tabs.area = chunks[0];
         Tabs::default()
-            .block(Block::default().borders(Borders::ALL).title(app.title))
+            .block(Block::default().borders(Borders::ALL).title(&title))
             .titles(&app.tabs.titles)
-            .style(Style::default().fg(Color::Green))
-            .highlight_style(Style::default().fg(Color::Yellow))
+            .style(app.theme.tabs)
+            .highlight_style(app.theme.selected_tab)
             .select(app.tabs.index)
             .render(&mut f, chunks[0]);
+        draw_dirty_marker(&mut f, app, chunks[0]);
         match app.tabs.index {
             0 => draw_first_tab(&mut f, app, chunks[1]),
             1 => draw_second_tab(&mut f, app, chunks[1]),
+            2 => draw_third_tab(&mut f, app, chunks[1]),
             _ => {}
         };
+        draw_status_bar(&mut f, app, chunks[2]);
+        match app.current_action {
+            CodePalAction::Search => {
+                let area = centered_rect(80, 70, f.size());
+                app.search.draw(&mut f, area, &app.theme);
+            }
+            CodePalAction::Help => {
+                let area = centered_rect(70, 80, f.size());
+                app.help.draw(&mut f, area, &app.theme);
+            }
+            _ => {}
+        }
     })
 }
 
-fn draw_first_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+/// Marks unsaved changes at the right end of the tab bar.
+fn draw_dirty_marker<B>(f: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    let marker = "● unsaved ";
+    let width = marker.chars().count() as u16;
+    if !app.dirty || area.width < width + 2 || area.height < 3 {
+        return;
+    }
+    let area = Rect::new(area.right() - 1 - width, area.y + 1, width, 1);
+    let text = [Text::styled(marker, app.theme.warning)];
+    Paragraph::new(text.iter()).render(f, area);
+}
+
+/// What is being done and where, whether there are unsaved changes, the last
+/// message and how to get help.
+fn draw_status_bar<B>(f: &mut Frame<B>, app: &App, area: Rect)
 where
     B: Backend,
 {
+    let theme = &app.theme;
+    let mut text = vec![];
+    if let Some(mode) = app.mode() {
+        let style = match mode {
+            Mode::Normal => theme.normal_mode,
+            Mode::Insert => theme.insert_mode,
+        };
+        text.push(Text::styled(format!(" {} ", mode.name()), style));
+        if let Some(pending) = app.modal.as_ref().and_then(|modal| modal.pending()) {
+            text.push(Text::raw(format!(" {}", pending)));
+        }
+    }
+    let doing = match app.focus() {
+        Some(focus) => format!(" {} ({})", app.current_action.label(), focus),
+        None => format!(" {}", app.current_action.label()),
+    };
+    text.push(Text::raw(doing));
+    text.push(match app.dirty {
+        true => Text::styled(" | unsaved changes", theme.warning),
+        false => Text::styled(" | saved", theme.muted),
+    });
+    if let Some(message) = &app.message {
+        text.push(Text::raw(format!(" | {}", message)));
+    }
+
+    let hint = match app.keymap.keys(Context::Global, Action::Help).first() {
+        Some(key) => format!("{} for help ", key),
+        None => String::new(),
+    };
     let chunks = Layout::default()
-        .constraints([Constraint::Length(10), Constraint::Min(7)].as_ref())
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Min(0), Constraint::Length(hint.len() as u16)].as_ref())
         .split(area);
+    Paragraph::new(text.iter()).render(f, chunks[0]);
+    let hint = [Text::styled(hint, theme.muted)];
+    Paragraph::new(hint.iter()).render(f, chunks[1]);
+}
 
-    let horizontal_chunks = Layout::default()
-        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)].as_ref())
+/// A rectangle of the given percentages of `area`, centered in it.
+fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    Layout::default()
         .direction(Direction::Horizontal)
-        .split(chunks[0]);
-    app.todo_items.draw(f, horizontal_chunks[0]);
-    app.todo_item_project.draw(f, horizontal_chunks[1]);
-    app.notes.draw(f, chunks[1]);
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(vertical[1])[1]
+}
+
+fn draw_first_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    // Panes go side by side, or one above the other on narrow terminals.
+    let direction = app.layout.direction(area.width);
+    let filtering = matches!(app.current_action, CodePalAction::Filter);
+    let area = if matches!(app.current_action, CodePalAction::Due) {
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
+            .split(area);
+        app.todo_filter.area = Rect::default();
+        app.due_editor.draw(f, chunks[0], &app.theme);
+        chunks[1]
+    } else if filtering || !app.todo_view.is_default() {
+        let height = match direction {
+            Direction::Horizontal => 3,
+            Direction::Vertical => 6,
+        };
+        let chunks = Layout::default()
+            .constraints([Constraint::Length(height), Constraint::Min(0)].as_ref())
+            .split(area);
+        draw_filter_bar(f, app, chunks[0], &direction);
+        chunks[1]
+    } else {
+        // Hidden, so not to be clicked.
+        app.todo_filter.area = Rect::default();
+        area
+    };
+    let layout = &app.layout;
+    let chunks = layout::split(&Direction::Vertical, layout.todo_height, area);
+    let todo_chunks = layout::split(&direction, layout.list_width, chunks[0]);
+    let note_chunks = layout::split(&direction, layout.list_width, chunks[1]);
+    let pane_chunks = layout::split(
+        &Direction::Vertical,
+        layout.description_height,
+        note_chunks[1],
+    );
+
+    app.todo_items.draw(f, todo_chunks[0], &app.theme);
+    app.todo_item_project.draw(f, todo_chunks[1], &app.theme);
+    draw_notes(f, app, note_chunks[0]);
+    app.description_pane.draw(f, pane_chunks[0], &app.theme);
+    app.note_pane.draw(f, pane_chunks[1], &app.theme);
+}
+
+/// The notes of the selected todo and, when it has one, its branch with
+/// the commits on it below.
+fn draw_notes<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let naming = matches!(app.current_action, CodePalAction::Branch);
+    if !naming && app.branch_editor.text.is_empty() {
+        app.branch_editor.area = Rect::default();
+        app.notes.draw(f, area, &app.theme);
+        return;
+    }
+    let chunks = Layout::default()
+        .constraints(
+            [
+                Constraint::Min(4),
+                Constraint::Length(3),
+                Constraint::Length(6),
+            ]
+            .as_ref(),
+        )
+        .split(area);
+    app.notes.draw(f, chunks[0], &app.theme);
+    app.branch_editor.draw(f, chunks[1], &app.theme);
+    draw_branch_commits(f, app, chunks[2]);
+}
+
+/// The commits on the branch of the selected todo that no other local
+/// branch has.
+fn draw_branch_commits<B>(f: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    let theme = &app.theme;
+    let source = app.branch_source();
+    let commits = source
+        .as_ref()
+        .and_then(|source| app.branch_commits.get(source));
+    let text = match (&source, commits) {
+        (None, _) if app.project_dir().is_none() => vec![Text::styled(
+            "the todo's project has no directory",
+            theme.muted,
+        )],
+        (None, _) => vec![Text::styled(
+            "no branch yet, enter checks it out",
+            theme.muted,
+        )],
+        (Some(_), None) => vec![Text::styled("looking at the branch…", theme.muted)],
+        (Some(_), Some(Err(error))) => vec![Text::styled(error.clone(), theme.error)],
+        (Some(_), Some(Ok(None))) => vec![Text::styled("not created yet", theme.muted)],
+        (Some(_), Some(Ok(Some(commits)))) if commits.is_empty() => {
+            vec![Text::styled("no commits of its own yet", theme.muted)]
+        }
+        (Some(_), Some(Ok(Some(commits)))) => {
+            let now = Utc::now();
+            commits
+                .iter()
+                .flat_map(|commit| {
+                    vec![
+                        Text::styled(format!("{} ", commit.hash), theme.accent),
+                        Text::raw(format!("{} ", commit.summary)),
+                        Text::styled(format!("({})\n", commit.age(now)), theme.muted),
+                    ]
+                })
+                .collect()
+        }
+    };
+    Paragraph::new(text.iter())
+        .block(Block::default().borders(Borders::ALL).title("Commits"))
+        .render(f, area);
+}
+
+fn draw_filter_bar<B>(f: &mut Frame<B>, app: &mut App, area: Rect, direction: &Direction)
+where
+    B: Backend,
+{
+    let chunks = layout::split(direction, 65, area);
+    app.todo_filter.draw(f, chunks[0], &app.theme);
+    let text = [Text::raw(app.todo_view.describe())];
+    Paragraph::new(text.iter())
+        .block(Block::default().borders(Borders::ALL).title("View"))
+        .render(f, chunks[1]);
 }
 
 fn draw_second_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
+where
+    B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(5)].as_ref())
+        .split(area);
+    app.projects.draw(f, chunks[0], &app.theme);
+    draw_repo_status(f, app, chunks[1]);
+}
+
+/// The git repository in the directory of the selected project.
+fn draw_repo_status<B>(f: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    let theme = &app.theme;
+    let selected = app.projects.current_selection.unwrap_or(0);
+    let project = app.app_state.projects.borrow().get(selected).cloned();
+    let status = project
+        .as_ref()
+        .and_then(|project| app.repo_statuses.get(&project.identifier));
+    let text = match (&project, status) {
+        (None, _) => vec![],
+        (Some(project), _) if project.dir_location.trim().is_empty() => {
+            vec![Text::styled("no directory set", theme.muted)]
+        }
+        (Some(_), None) => vec![Text::styled("looking at the repository…", theme.muted)],
+        (Some(_), Some(Err(error))) => vec![Text::styled(error.clone(), theme.error)],
+        (Some(_), Some(Ok(status))) => {
+            let mut text = vec![match &status.branch {
+                Some(branch) => Text::styled(branch.clone(), theme.accent),
+                None => Text::styled("detached HEAD", theme.warning),
+            }];
+            text.push(Text::raw(match status.ahead_behind {
+                Some((ahead, behind)) => format!("  ↑{} ↓{}", ahead, behind),
+                None => String::from("  no upstream"),
+            }));
+            text.push(match status.dirty {
+                true => Text::styled("  uncommitted changes\n", theme.warning),
+                false => Text::styled("  clean\n", theme.success),
+            });
+            text.push(match &status.last_commit {
+                Some(commit) => Text::raw(format!(
+                    "{} {} ({})",
+                    commit.hash,
+                    commit.summary,
+                    commit.age(Utc::now())
+                )),
+                None => Text::styled("no commits yet", theme.muted),
+            });
+            text
+        }
+    };
+    Paragraph::new(text.iter())
+        .block(Block::default().borders(Borders::ALL).title("Repository"))
+        .wrap(true)
+        .render(f, area);
+}
+
+fn draw_third_tab<B>(f: &mut Frame<B>, app: &mut App, area: Rect)
 where
     B: Backend,
 {
     let chunks = Layout::default()
         .constraints([Constraint::Min(7)].as_ref())
         .split(area);
-    app.projects.draw(f, chunks[0]);
+    app.workspace_switcher.draw(f, chunks[0], &app.theme);
 }
@@ -1,15 +1,23 @@
+use crate::app::history::{Command, EditKind, InsertRow, RemoveRow, TextEdit};
+use crate::app::keymap::Action;
+use crate::app::layout::ColumnWidth;
+use crate::app::matcher::{FuzzyMatcher, Matcher};
+use crate::app::text_buffer::{Edit, KeyResult, TextBuffer, TextInput};
+use crate::app::theme::{Styled, Theme};
+use crate::app::viewport::{Scrollbar, Viewport, WHEEL_ROWS};
 use crate::app::{ActionPayload, Event};
-use clipboard::ClipboardContext;
-use clipboard::ClipboardProvider;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm::event::{KeyCode, KeyEvent, MouseButton, MouseEvent};
 use std::cell::RefCell;
+use std::cmp::Reverse;
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
 use tui::backend::Backend;
+use tui::buffer::Buffer;
 use tui::layout::{Constraint, Layout, Rect};
-use tui::style::{Color, Modifier, Style};
-use tui::widgets::{Block, Borders, Paragraph, Row, SelectableList, Table, Text, Widget};
+use tui::style::{Modifier, Style};
+use tui::widgets::{Block, Borders, Paragraph, Row, Table, Text, Widget};
 use tui::Frame;
+use unicode_width::UnicodeWidthStr;
 
 use crate::todo::todo::{EditableRowItem, EditableStateItem, SelectableItem};
 
@@ -17,28 +25,120 @@ pub trait UIEventProcessor {
 	fn on_deactivate(&mut self) {}
 	fn on_activate(&mut self) {}
 	fn on_event(&mut self, _: KeyEvent) {}
+	/// Handles a click or a turn of the wheel over where it was last drawn.
+	fn on_mouse(&mut self, _: MouseEvent) {}
+	/// Performs what a key was bound to in the keymap.
+	fn on_action(&mut self, _: Action) {}
+	/// Hands the commands applied since the last call over to the history.
+	fn take_changes(&mut self) -> Vec<Box<dyn Command>> {
+		vec![]
+	}
 }
 
 pub trait UIComponent {
-	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect)
+	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
 	where
 		B: Backend;
 }
 
+/// Whether the cell at `column`, `row` is within `area`.
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+	column >= area.left() && column < area.right() && row >= area.top() && row < area.bottom()
+}
+
+/// The line of a list drawn in `area`, below `skip` lines of headers, that
+/// is shown at `column`, `row`.
+fn clicked_line(
+	area: Rect,
+	skip: u16,
+	viewport: &Viewport,
+	column: u16,
+	row: u16,
+) -> Option<usize> {
+	let lines = inner_rows(area, skip);
+	let within_borders = column > area.left() && column + 1 < area.right();
+	match within_borders && contains(lines, column, row) {
+		true => viewport.line_at((row - lines.top()) as usize),
+		false => None,
+	}
+}
+
+/// Blanks an area, so that an overlay hides whatever was drawn below it.
+pub struct Clear;
+
+impl Widget for Clear {
+	fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+		for y in area.top()..area.bottom() {
+			for x in area.left()..area.right() {
+				buf.get_mut(x, y).reset();
+			}
+		}
+	}
+}
+
+/// A row of a list view, either a group title or the index of an item in the
+/// underlying list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViewRow {
+	Group(String),
+	Item(usize),
+}
+
 pub struct ListTextEditor<T>
 where
 	T: EditableStateItem,
 {
 	pub title: String,
 	pub current_text: Option<Rc<RefCell<Vec<T>>>>,
+	/// Always an index into `current_text`, whatever the view shows.
 	pub current_selection: Option<usize>,
+	/// The rows to show instead of the whole list in its own order.
+	pub view: Option<Vec<ViewRow>>,
+	/// The cursor within the selected item.
+	pub buffer: TextBuffer,
+	/// The lines of the rows in view.
+	pub viewport: Viewport,
+	/// Where it was last drawn, for the mouse.
+	pub area: Rect,
 	pub active: bool,
 	pub sender: Sender<Event>,
+	pub changes: Vec<Box<dyn Command>>,
 }
 
 impl<T> ListTextEditor<T>
 where
 	T: EditableStateItem,
+{
+	fn rows(&self) -> Vec<ViewRow> {
+		match (&self.view, &self.current_text) {
+			(Some(view), _) => view.clone(),
+			(None, Some(x)) => (0..x.borrow().len()).map(ViewRow::Item).collect(),
+			(None, None) => vec![],
+		}
+	}
+
+	fn visible_items(&self) -> Vec<usize> {
+		self.rows()
+			.into_iter()
+			.filter_map(|row| match row {
+				ViewRow::Item(i) => Some(i),
+				ViewRow::Group(_) => None,
+			})
+			.collect()
+	}
+}
+
+fn list_content<T: EditableStateItem>(item: &mut T, _: usize) -> &mut String {
+	item.get_content_mut()
+}
+
+fn row_content<T: EditableRowItem>(item: &mut T, column: usize) -> &mut String {
+	item.get_content_mut(column)
+}
+
+impl<T> ListTextEditor<T>
+where
+	T: EditableStateItem + 'static,
 {
 	pub fn new(
 		title: String,
@@ -49,83 +149,185 @@ where
 			title,
 			current_text: initial_text,
 			current_selection: Option::None,
+			view: None,
+			buffer: TextBuffer::new(),
+			viewport: Viewport::default(),
+			area: Rect::default(),
 			active: false,
 			sender,
+			changes: vec![],
 		}
 	}
 
-	pub fn on_key(&mut self, c: char, _: KeyModifiers) {
-		let selected_index = match self.current_selection {
-			Some(selected_index) => selected_index,
-			None => 0,
-		};
-		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		if let Some(elem) = item_ref.borrow_mut().get_mut(selected_index) {
-			let content = elem.get_content_mut();
-			content.push(c);
-		};
+	fn perform(&mut self, mut command: Box<dyn Command>) {
+		command.apply();
+		self.changes.push(command);
 	}
 
-	pub fn on_paste(&mut self) {
-		let selected_index = match self.current_selection {
-			Some(selected_index) => selected_index,
-			None => 0,
-		};
-		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		if let Some(elem) = item_ref.borrow_mut().get_mut(selected_index) {
-			let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-			let content = elem.get_content_mut();
-			if let Ok(received) = ctx.get_contents() {
-				content.push_str(&received);
-			}
+	/// Puts the cursor on the selected row, returns the row and its content.
+	fn attach_cursor(&mut self) -> Option<(usize, String)> {
+		let index = self.current_selection?;
+		let list = (*self.current_text.as_ref()?).clone();
+		let text = list.borrow_mut().get_mut(index)?.get_content_mut().clone();
+		self.buffer.attach((index, 0), &text);
+		Some((index, text))
+	}
+
+	fn edit_text(&mut self, index: usize, edit: Edit, kind: EditKind) {
+		let list = (*self.current_text.as_ref().unwrap()).clone();
+		self.perform(Box::new(TextEdit {
+			list,
+			index,
+			field: 0,
+			accessor: list_content::<T>,
+			position: edit.position,
+			removed: edit.removed,
+			inserted: edit.inserted,
+			kind,
+		}));
+	}
+
+	/// Hands `input` to the cursor of the selected row, returns whether it
+	/// was a text editing key.
+	pub fn on_text_event(&mut self, input: impl Into<TextInput>) -> bool {
+		let (index, text) = match self.attach_cursor() {
+			Some(selected) => selected,
+			None => return false,
 		};
+		match self.buffer.on_input(&text, input) {
+			KeyResult::Edited(edit, kind) => self.edit_text(index, edit, kind),
+			KeyResult::Handled => {}
+			KeyResult::Ignored => return false,
+		}
+		true
 	}
 
 	pub fn on_up(&mut self) {
-		self.select_item(match self.current_selection {
-			Some(x) if x > 0 => x - 1,
-			_ => 0,
+		let visible = self.visible_items();
+		let position = self
+			.current_selection
+			.and_then(|x| visible.iter().position(|i| *i == x));
+		self.select_item(match position {
+			Some(p) if p > 0 => visible[p - 1],
+			_ => visible.first().cloned().unwrap_or(0),
 		});
 	}
 	pub fn on_down(&mut self) {
 		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		let mut borrowed_item = item_ref.borrow_mut();
-		self.select_item(match self.current_selection {
-			Some(x) if x < borrowed_item.len() - 1 => x + 1,
-			Some(x) if x == borrowed_item.len() - 1 => {
-				let t = T::new(String::from(""));
-				borrowed_item.push(t);
-				borrowed_item.len() - 1
+		let len = item_ref.borrow().len();
+		let visible = self.visible_items();
+		let position = self
+			.current_selection
+			.and_then(|x| visible.iter().position(|i| *i == x));
+		match position {
+			Some(p) if p + 1 < visible.len() => self.select_item(visible[p + 1]),
+			Some(_) => {
+				self.perform(Box::new(InsertRow {
+					list: item_ref,
+					index: len,
+					item: Some(T::new(String::from(""))),
+				}));
+				self.select_item(len);
 			}
-			_ => borrowed_item.len(),
-		});
+			None => self.select_item(visible.first().cloned().unwrap_or(len)),
+		}
 	}
-	pub fn on_backspace(&mut self) {
+	/// Removes the selected row if it's empty, otherwise deletes backwards.
+	pub fn on_backspace(&mut self, event: KeyEvent) {
 		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		match self.current_selection {
-			Some(x) => {
-				let mut borrowed_item = item_ref.borrow_mut();
-				if let Some(elem) = borrowed_item.get_mut(x) {
-					let content = elem.get_content_mut();
-					if content.len() > 0 {
-						content.pop();
-					} else {
-						if x >= 1 {
-							borrowed_item.remove(x);
-							self.current_selection = Some(x - 1);
-						}
-					}
-				}
+		if let Some(x) = self.current_selection {
+			let empty = match item_ref.borrow_mut().get_mut(x) {
+				Some(elem) => elem.get_content_mut().is_empty(),
+				None => return,
+			};
+			if !empty {
+				self.on_text_event(event);
+			} else if x >= 1 {
+				self.perform(Box::new(RemoveRow {
+					list: item_ref,
+					index: x,
+					item: None,
+				}));
+				self.current_selection = Some(x - 1);
 			}
-			_ => {}
 		}
 	}
 	pub fn on_enter(&mut self) {
 		if let Some(x) = self.current_selection {
-			let t = T::new(String::from(""));
-			let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-			item_ref.borrow_mut().insert(x + 1, t);
-			self.select_item(x + 1);
+			self.insert_row(x + 1);
+		}
+	}
+	pub fn on_insert_above(&mut self) {
+		if let Some(x) = self.current_selection {
+			self.insert_row(x);
+		}
+	}
+	fn insert_row(&mut self, index: usize) {
+		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
+		self.perform(Box::new(InsertRow {
+			list: item_ref,
+			index,
+			item: Some(T::new(String::from(""))),
+		}));
+		self.select_item(index);
+	}
+	/// Selects the next visible row, without adding one after the last.
+	pub fn on_next(&mut self) {
+		let visible = self.visible_items();
+		let position = self
+			.current_selection
+			.and_then(|x| visible.iter().position(|i| *i == x));
+		match position {
+			Some(p) if p + 1 < visible.len() => self.select_item(visible[p + 1]),
+			Some(_) => {}
+			None => self.on_first(),
+		}
+	}
+	/// Selects the visible row `rows` below the selected one, or above it
+	/// when negative, stopping at the first and the last.
+	fn move_by(&mut self, rows: isize) {
+		let visible = self.visible_items();
+		if visible.is_empty() {
+			return;
+		}
+		let position = self
+			.current_selection
+			.and_then(|x| visible.iter().position(|i| *i == x))
+			.unwrap_or(0) as isize;
+		let target = (position + rows).max(0).min(visible.len() as isize - 1);
+		self.select_item(visible[target as usize]);
+	}
+	pub fn on_page_up(&mut self) {
+		self.move_by(-(self.viewport.page() as isize));
+	}
+	pub fn on_page_down(&mut self) {
+		self.move_by(self.viewport.page() as isize);
+	}
+	pub fn on_first(&mut self) {
+		if let Some(first) = self.visible_items().first() {
+			self.select_item(*first);
+		}
+	}
+	pub fn on_last(&mut self) {
+		if let Some(last) = self.visible_items().last() {
+			self.select_item(*last);
+		}
+	}
+	/// Removes the selected row whatever it holds, as long as it isn't the
+	/// only one.
+	pub fn on_delete_row(&mut self) {
+		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
+		let len = item_ref.borrow().len();
+		match self.current_selection {
+			Some(x) if x < len && len > 1 => {
+				self.perform(Box::new(RemoveRow {
+					list: item_ref,
+					index: x,
+					item: None,
+				}));
+				self.select_item(x.min(len - 2));
+			}
+			_ => {}
 		}
 	}
 
@@ -151,7 +353,7 @@ where
 
 impl<T> UIEventProcessor for ListTextEditor<T>
 where
-	T: EditableStateItem,
+	T: EditableStateItem + 'static,
 {
 	fn on_deactivate(&mut self) {
 		self.active = false
@@ -166,23 +368,60 @@ where
 		if let Some(_) = self.current_text {
 			if self.active {
 				match (event.code, event.modifiers) {
-					(KeyCode::Char('v'), KeyModifiers::CONTROL) => self.on_paste(),
-					(KeyCode::Char(c), _) => self.on_key(c, event.modifiers),
-					(KeyCode::Up, _) => self.on_up(),
-					(KeyCode::Down, _) => self.on_down(),
-					(KeyCode::Backspace, _) => self.on_backspace(),
-					(KeyCode::Enter, _) => self.on_enter(),
-					(_, _) => {}
+					(KeyCode::Backspace, m) if m.is_empty() => self.on_backspace(event),
+					(_, _) => {
+						self.on_text_event(event);
+					}
 				}
 			}
 		}
 	}
+	fn on_mouse(&mut self, event: MouseEvent) {
+		if self.current_text.is_none() {
+			return;
+		}
+		match event {
+			MouseEvent::Down(MouseButton::Left, column, row, _) => {
+				let line = clicked_line(self.area, 0, &self.viewport, column, row);
+				let clicked = line.and_then(|line| self.rows().get(line).cloned());
+				if let Some(ViewRow::Item(i)) = clicked {
+					self.select_item(i);
+				}
+			}
+			MouseEvent::ScrollUp(..) => self.move_by(-(WHEEL_ROWS as isize)),
+			MouseEvent::ScrollDown(..) => self.move_by(WHEEL_ROWS as isize),
+			_ => {}
+		}
+	}
+	fn on_action(&mut self, action: Action) {
+		if self.current_text.is_some() && self.active {
+			match action {
+				Action::Up => self.on_up(),
+				Action::Down => self.on_down(),
+				Action::Next => self.on_next(),
+				Action::PageUp => self.on_page_up(),
+				Action::PageDown => self.on_page_down(),
+				Action::First => self.on_first(),
+				Action::Last => self.on_last(),
+				Action::InsertRow => self.on_enter(),
+				Action::InsertRowAbove => self.on_insert_above(),
+				Action::DeleteRow => self.on_delete_row(),
+				action if action.edits_text() => {
+					self.on_text_event(action);
+				}
+				_ => {}
+			}
+		}
+	}
+	fn take_changes(&mut self) -> Vec<Box<dyn Command>> {
+		std::mem::take(&mut self.changes)
+	}
 }
 impl<T> UIComponent for ListTextEditor<T>
 where
-	T: EditableStateItem,
+	T: EditableStateItem + Styled + 'static,
 {
-	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect)
+	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
 	where
 		B: Backend,
 	{
@@ -190,26 +429,87 @@ where
 			true => ">",
 			false => "*",
 		};
+		self.area = area;
+		if self.active {
+			self.attach_cursor();
+		}
 		if let Some(x) = self.current_text.as_ref() {
 			let item_ref = (*x).clone();
+			let rows = self.rows();
 			let mut borrowed_item = item_ref.borrow_mut();
 
-			let items: Vec<_> = borrowed_item
-				.iter_mut()
-				.map(|x| (x.get_content_mut()))
-				.collect();
+			let highlight_style = theme.selection;
+			let group_style = theme.group;
+			let current_selection = self.current_selection;
+			// Labels take one line, so the viewport counts rows.
+			let len = rows.len();
+			let selected_row = current_selection
+				.and_then(|s| rows.iter().position(|row| *row == ViewRow::Item(s)));
+			let mut lines = vec![];
+			for row in rows {
+				match row {
+					ViewRow::Group(name) => lines.push(Text::styled(name + "\n", group_style)),
+					ViewRow::Item(i) => {
+						let x = match borrowed_item.get_mut(i) {
+							Some(x) => x,
+							None => {
+								lines.push(Text::raw("\n"));
+								continue;
+							}
+						};
+						match current_selection {
+							Some(s) if s == i && self.active && !x.get_content_mut().contains('\n') => {
+								// Show the cursor within the content.
+								let (prefix, suffix) = x.get_decorations();
+								let prefix = format!("{} {}", selection_symbol, prefix);
+								lines.push(Text::styled(prefix, highlight_style));
+								let content = x.get_content_mut().clone();
+								lines.extend(self.buffer.spans(&content, highlight_style));
+								lines.push(Text::styled(suffix + "\n", highlight_style));
+							}
+							Some(s) if s == i => lines.push(Text::styled(
+								format!("{} {}\n", selection_symbol, x.get_label()),
+								highlight_style,
+							)),
+							Some(_) => lines.push(Text::styled(
+								format!("  {}\n", x.get_label()),
+								x.style(theme),
+							)),
+							None => {
+								lines.push(Text::styled(format!("{}\n", x.get_label()), x.style(theme)))
+							}
+						}
+					}
+				}
+			}
 
-			SelectableList::default()
+			let height = area.height.saturating_sub(2) as usize;
+			let selected = selected_row.map(|row| (row, row + 1));
+			self.viewport.follow(height, len, selected);
+			Paragraph::new(lines.iter())
 				.block(Block::default().borders(Borders::ALL).title(&self.title))
-				.items(&items)
-				.select(self.current_selection)
-				.highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
-				.highlight_symbol(selection_symbol)
+				.scroll(self.viewport.offset as u16)
 				.render(f, area);
+			Scrollbar {
+				viewport: self.viewport,
+				len,
+				style: theme.accent,
+			}
+			.render(f, inner_rows(area, 0));
 		}
 	}
 }
 
+/// The rows of `area` inside its borders, less the first `skip`.
+fn inner_rows(area: Rect, skip: u16) -> Rect {
+	let top = (area.y + 1 + skip).min(area.bottom());
+	Rect {
+		y: top,
+		height: area.bottom().saturating_sub(1).saturating_sub(top),
+		..area
+	}
+}
+
 pub struct TableEditor<T>
 where
 	T: EditableRowItem,
@@ -218,21 +518,28 @@ where
 	pub current_text: Option<Rc<RefCell<Vec<T>>>>,
 	pub current_selection: Option<usize>,
 	pub current_header_selection: Option<usize>,
-	pub column_lengths: Vec<u16>,
+	pub column_widths: Vec<ColumnWidth>,
 	pub headers: Vec<String>,
+	/// The cursor within the selected cell.
+	pub buffer: TextBuffer,
+	/// The rows in view, below the headers.
+	pub viewport: Viewport,
+	/// Where it was last drawn, for the mouse.
+	pub area: Rect,
 	pub active: bool,
 	pub sender: Sender<Event>,
+	pub changes: Vec<Box<dyn Command>>,
 }
 
 impl<T> TableEditor<T>
 where
-	T: EditableRowItem,
+	T: EditableRowItem + 'static,
 {
 	pub fn new(
 		title: String,
 		initial_text: Option<Rc<RefCell<Vec<T>>>>,
 		headers: Vec<String>,
-		column_lengths: Vec<u16>,
+		column_widths: Vec<ColumnWidth>,
 		sender: Sender<Event>,
 	) -> TableEditor<T> {
 		TableEditor {
@@ -240,10 +547,68 @@ where
 			current_text: initial_text,
 			current_selection: Option::None,
 			current_header_selection: Option::None,
-			column_lengths,
+			column_widths,
 			headers,
+			buffer: TextBuffer::new(),
+			viewport: Viewport::default(),
+			area: Rect::default(),
 			active: false,
 			sender,
+			changes: vec![],
+		}
+	}
+	fn perform(&mut self, mut command: Box<dyn Command>) {
+		command.apply();
+		self.changes.push(command);
+	}
+	/// Puts the cursor on the selected cell, returns the row, the column and
+	/// the content of the cell.
+	fn attach_cursor(&mut self) -> Option<(usize, usize, String)> {
+		let index = self.current_selection.unwrap_or(0);
+		let column = self.current_header_selection.unwrap_or(0);
+		let list = (*self.current_text.as_ref()?).clone();
+		let text = list.borrow_mut().get_mut(index)?.get_content_mut(column).clone();
+		self.buffer.attach((index, column), &text);
+		Some((index, column, text))
+	}
+	fn edit_text(&mut self, index: usize, column: usize, edit: Edit, kind: EditKind) {
+		let list = (*self.current_text.as_ref().unwrap()).clone();
+		self.perform(Box::new(TextEdit {
+			list,
+			index,
+			field: column,
+			accessor: row_content::<T>,
+			position: edit.position,
+			removed: edit.removed,
+			inserted: edit.inserted,
+			kind,
+		}));
+	}
+	/// Hands `input` to the cursor of the selected cell. Left at the start
+	/// and right at the end of a cell move to the neighbouring column.
+	pub fn on_text_event(&mut self, input: impl Into<TextInput>) {
+		let (index, column, text) = match self.attach_cursor() {
+			Some(selected) => selected,
+			None => return,
+		};
+		let input = input.into();
+		let key = match input {
+			TextInput::Key(event) => Some((event.code, event.modifiers)),
+			TextInput::Action(_) => None,
+		};
+		match key {
+			Some((KeyCode::Left, m)) if m.is_empty() && self.buffer.at_start() => self.on_left(),
+			Some((KeyCode::Right, m)) if m.is_empty() && self.buffer.at_end(&text) => {
+				self.on_right();
+				if self.attach_cursor().is_some() {
+					self.buffer.move_start_of_text();
+				}
+			}
+			_ => {
+				if let KeyResult::Edited(edit, kind) = self.buffer.on_input(&text, input) {
+					self.edit_text(index, column, edit, kind);
+				}
+			}
 		}
 	}
 	pub fn select_item(&mut self, index: usize) {
@@ -252,22 +617,9 @@ where
 	pub fn select_header(&mut self, index: usize) {
 		self.current_header_selection = Some(index);
 	}
-	pub fn on_key(&mut self, c: char, _: KeyModifiers) {
-		let selected_index = match self.current_selection {
-			Some(selected_index) => selected_index,
-			None => 0,
-		};
-		let selected_header = match self.current_header_selection {
-			Some(selected_header) => selected_header,
-			None => 0,
-		};
-		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		if let Some(elem) = item_ref.borrow_mut().get_mut(selected_index) {
-			let content = elem.get_content_mut(selected_header);
-			content.push(c);
-		};
-	}
-	pub fn on_backspace(&mut self) {
+	/// Removes the selected row if its first cell is selected and empty,
+	/// otherwise deletes backwards.
+	pub fn on_backspace(&mut self, event: KeyEvent) {
 		let selected_index = match self.current_selection {
 			Some(selected_index) => selected_index,
 			None => 0,
@@ -277,20 +629,20 @@ where
 			None => 0,
 		};
 		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		let mut borrowed_item = item_ref.borrow_mut();
-		if let Some(elem) = borrowed_item.get_mut(selected_index) {
-			let content = elem.get_content_mut(selected_header);
-			if content.len() > 0 {
-				content.pop();
-			} else {
-				if selected_header == 0 {
-					if selected_index >= 1 {
-						borrowed_item.remove(selected_index);
-						self.current_selection = Some(selected_index - 1);
-					}
-				}
-			}
+		let empty = match item_ref.borrow_mut().get_mut(selected_index) {
+			Some(elem) => elem.get_content_mut(selected_header).is_empty(),
+			None => return,
 		};
+		if !empty {
+			self.on_text_event(event);
+		} else if selected_header == 0 && selected_index >= 1 {
+			self.perform(Box::new(RemoveRow {
+				list: item_ref,
+				index: selected_index,
+				item: None,
+			}));
+			self.current_selection = Some(selected_index - 1);
+		}
 	}
 	pub fn on_up(&mut self) {
 		self.select_item(match self.current_selection {
@@ -300,16 +652,46 @@ where
 	}
 	pub fn on_down(&mut self) {
 		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		let mut borrowed_item = item_ref.borrow_mut();
-		self.select_item(match self.current_selection {
-			Some(x) if x < borrowed_item.len() - 1 => x + 1,
-			Some(x) if x == borrowed_item.len() - 1 => {
-				let t = T::new(vec![]);
-				borrowed_item.push(t);
-				borrowed_item.len() - 1
+		let len = item_ref.borrow().len();
+		match self.current_selection {
+			Some(x) if x + 1 < len => self.select_item(x + 1),
+			Some(x) if x + 1 == len => {
+				self.perform(Box::new(InsertRow {
+					list: item_ref,
+					index: len,
+					item: Some(T::new(vec![])),
+				}));
+				self.select_item(len);
 			}
-			_ => borrowed_item.len(),
-		});
+			_ => self.select_item(len),
+		}
+	}
+
+	/// Selects the row `rows` below the selected one, or above it when
+	/// negative, stopping at the first and the last.
+	fn move_by(&mut self, rows: isize) {
+		let len = match self.current_text.as_ref() {
+			Some(x) => x.borrow().len(),
+			None => return,
+		};
+		let position = self.current_selection.unwrap_or(0) as isize;
+		let target = (position + rows).min(len as isize - 1).max(0);
+		self.select_item(target as usize);
+	}
+	pub fn on_page_up(&mut self) {
+		self.move_by(-(self.viewport.page() as isize));
+	}
+	pub fn on_page_down(&mut self) {
+		self.move_by(self.viewport.page() as isize);
+	}
+	pub fn on_first(&mut self) {
+		self.select_item(0);
+	}
+	pub fn on_last(&mut self) {
+		if let Some(x) = self.current_text.as_ref() {
+			let len = x.borrow().len();
+			self.select_item(len.saturating_sub(1));
+		}
 	}
 
 	pub fn on_left(&mut self) {
@@ -346,29 +728,11 @@ where
 			}
 		};
 	}
-	pub fn on_paste(&mut self) {
-		let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-		let selected_index = match self.current_selection {
-			Some(selected_index) => selected_index,
-			None => 0,
-		};
-		let selected_header = match self.current_header_selection {
-			Some(selected_header) => selected_header,
-			None => 0,
-		};
-		let item_ref = (*self.current_text.as_ref().unwrap()).clone();
-		if let Some(elem) = item_ref.borrow_mut().get_mut(selected_index) {
-			let content = elem.get_content_mut(selected_header);
-			if let Ok(received) = ctx.get_contents() {
-				content.push_str(&received);
-			}
-		};
-	}
 }
 
 impl<T> UIEventProcessor for TableEditor<T>
 where
-	T: EditableRowItem,
+	T: EditableRowItem + 'static,
 {
 	fn on_deactivate(&mut self) {
 		self.active = false
@@ -382,24 +746,55 @@ where
 		if let Some(_) = self.current_text {
 			if self.active {
 				match (event.code, event.modifiers) {
-					(KeyCode::Char('v'), KeyModifiers::CONTROL) => self.on_paste(),
-					(KeyCode::Char(c), _) => self.on_key(c, event.modifiers),
-					(KeyCode::Backspace, _) => self.on_backspace(),
-					(KeyCode::Up, _) => self.on_up(),
-					(KeyCode::Down, _) => self.on_down(),
-					(KeyCode::Right, _) => self.on_right(),
-					(KeyCode::Left, _) => self.on_left(),
-					(_, _) => {}
+					(KeyCode::Backspace, m) if m.is_empty() => self.on_backspace(event),
+					(_, _) => self.on_text_event(event),
 				}
 			}
 		}
 	}
+	fn on_mouse(&mut self, event: MouseEvent) {
+		let len = match self.current_text.as_ref() {
+			Some(x) => x.borrow().len(),
+			None => return,
+		};
+		match event {
+			MouseEvent::Down(MouseButton::Left, column, row, _) => {
+				// Below the headers and the gap after them.
+				match clicked_line(self.area, 2, &self.viewport, column, row) {
+					Some(line) if line < len => self.select_item(line),
+					_ => {}
+				}
+			}
+			MouseEvent::ScrollUp(..) => self.move_by(-(WHEEL_ROWS as isize)),
+			MouseEvent::ScrollDown(..) => self.move_by(WHEEL_ROWS as isize),
+			_ => {}
+		}
+	}
+	fn on_action(&mut self, action: Action) {
+		if self.current_text.is_some() && self.active {
+			match action {
+				Action::Up => self.on_up(),
+				Action::Down => self.on_down(),
+				Action::PageUp => self.on_page_up(),
+				Action::PageDown => self.on_page_down(),
+				Action::First => self.on_first(),
+				Action::Last => self.on_last(),
+				Action::NextColumn => self.on_right(),
+				Action::PreviousColumn => self.on_left(),
+				action if action.edits_text() => self.on_text_event(action),
+				_ => {}
+			}
+		}
+	}
+	fn take_changes(&mut self) -> Vec<Box<dyn Command>> {
+		std::mem::take(&mut self.changes)
+	}
 }
 impl<T> UIComponent for TableEditor<T>
 where
-	T: EditableRowItem,
+	T: EditableRowItem + 'static,
 {
-	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect)
+	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
 	where
 		B: Backend,
 	{
@@ -411,39 +806,85 @@ where
 			Some(selected_header) => selected_header,
 			None => 0,
 		};
+		self.area = area;
+		if self.active {
+			self.attach_cursor();
+		}
+		let (active, buffer) = (self.active, &self.buffer);
 		if let Some(x) = self.current_text.as_ref() {
 			let item_ref = (*x).clone();
 			let mut borrowed_item = item_ref.borrow_mut();
-			let rows = borrowed_item.iter_mut().enumerate().map(|(i, elem)| {
-				let style = match selected_index {
-					x if x == i => Style::default().fg(Color::Yellow),
-					_ => Style::default(),
-				};
-				let content_to_show: Vec<_> = elem
-					.get_content_vector()
-					.into_iter()
-					.enumerate()
-					.map(|(ii, s)| match (selected_index, selected_header) {
-						(x, y) if x == i && y == ii => format!(">{}", s),
-						_ => s.to_string(),
-					})
-					.collect();
-				Row::StyledData(content_to_show.into_iter(), style)
-			});
+			let rows: Vec<_> = borrowed_item
+				.iter_mut()
+				.enumerate()
+				.map(|(i, elem)| {
+					let style = match selected_index {
+						x if x == i => theme.selection,
+						_ => Style::default(),
+					};
+					let content_to_show: Vec<_> = elem
+						.get_content_vector()
+						.into_iter()
+						.enumerate()
+						.map(|(ii, s)| match (selected_index, selected_header) {
+							(x, y) if x == i && y == ii && active => {
+								format!(">{}", buffer.marked(s))
+							}
+							(x, y) if x == i && y == ii => format!(">{}", s),
+							_ => s.to_string(),
+						})
+						.collect();
+					(content_to_show, style)
+				})
+				.collect();
+			// What automatic columns need to show their widest cell.
+			let mut content_widths: Vec<_> = self.headers.iter().map(|h| h.width()).collect();
+			for (cells, _) in &rows {
+				for (width, cell) in content_widths.iter_mut().zip(cells) {
+					*width = (*width).max(cell.width());
+				}
+			}
+			let inner_width = area.width.saturating_sub(2);
 			let constraints: Vec<_> = self
-				.column_lengths
+				.column_widths
 				.iter()
-				.map(|l| Constraint::Length(*l))
+				.zip(content_widths)
+				.map(|(w, content)| {
+					w.constraint(content.min(u16::MAX as usize) as u16 + 1, inner_width)
+				})
 				.collect();
+			// The headers and the gap below them take two lines.
+			let len = rows.len();
+			let height = area.height.saturating_sub(4) as usize;
+			let selected = Some((selected_index, selected_index + 1));
+			self.viewport.follow(height, len, selected);
+			let rows = rows
+				.into_iter()
+				.skip(self.viewport.offset)
+				.map(|(cells, style)| Row::StyledData(cells.into_iter(), style));
 			Table::new(self.headers.iter(), rows)
 				.block(Block::default().title(&self.title).borders(Borders::ALL))
-				.header_style(Style::default().fg(Color::Yellow))
+				.header_style(theme.header)
 				.widths(&constraints)
 				.render(f, area);
+			Scrollbar {
+				viewport: self.viewport,
+				len,
+				style: theme.accent,
+			}
+			.render(f, inner_rows(area, 2));
 		}
 	}
 }
 
+/// A candidate offered by an `AutoCompleteEditor`, with the characters of
+/// its name that matched the text typed so far.
+#[derive(Debug, Clone)]
+pub struct Suggestion<T> {
+	pub item: T,
+	pub positions: Vec<usize>,
+}
+
 pub struct AutoCompleteEditor<T>
 where
 	T: SelectableItem,
@@ -451,47 +892,106 @@ where
 	pub active: bool,
 	pub title: String,
 	pub text: String,
-	pub current_suggestions: Vec<T>,
+	/// Everything that can be chosen, `current_suggestions` holds the ones
+	/// matching `text`, best first.
+	pub items: Vec<T>,
+	pub current_suggestions: Vec<Suggestion<T>>,
 	pub current_selection: Option<usize>,
 	pub current_chosen: Option<String>,
+	pub matcher: Box<dyn Matcher>,
+	pub buffer: TextBuffer,
+	/// The suggestions in view.
+	pub viewport: Viewport,
+	/// Where it was last drawn, for the mouse.
+	pub area: Rect,
 	pub sender: Sender<Event>,
 }
 
 impl<T> AutoCompleteEditor<T>
 where
-	T: SelectableItem,
+	T: SelectableItem + Clone,
 {
 	pub fn new(
 		title: String,
 		text: String,
-		current_suggestions: Vec<T>,
+		items: Vec<T>,
 		sender: Sender<Event>,
 	) -> AutoCompleteEditor<T> {
-		AutoCompleteEditor {
+		let mut editor = AutoCompleteEditor {
 			active: false,
 			title,
 			text,
-			current_suggestions,
+			items,
+			current_suggestions: vec![],
 			current_selection: None,
 			current_chosen: None,
+			matcher: Box::new(FuzzyMatcher),
+			buffer: TextBuffer::new(),
+			viewport: Viewport::default(),
+			area: Rect::default(),
 			sender,
+		};
+		editor.filter();
+		editor
+	}
+
+	pub fn with_matcher(mut self, matcher: Box<dyn Matcher>) -> AutoCompleteEditor<T> {
+		self.matcher = matcher;
+		self.filter();
+		self
+	}
+
+	pub fn set_items(&mut self, items: Vec<T>) {
+		self.items = items;
+		self.filter();
+	}
+
+	/// Ranks the items against `text` and selects the best one.
+	pub fn filter(&mut self) {
+		let mut ranked = vec![];
+		for (i, item) in self.items.iter_mut().enumerate() {
+			if let Some(m) = self.matcher.matches(&self.text, &item.get_name()) {
+				let suggestion = Suggestion {
+					item: item.clone(),
+					positions: m.positions,
+				};
+				ranked.push((m.score, i, suggestion));
+			}
 		}
+		ranked.sort_by_key(|(score, i, _)| (Reverse(*score), *i));
+		self.current_suggestions = ranked.into_iter().map(|(_, _, s)| s).collect();
+		self.select_suggestion(self.current_suggestions.first().map(|_| 0));
 	}
 
-	pub fn on_key(&mut self, c: char, _: KeyModifiers) {
-		self.text.push(c);
-		self.broadcast_text();
+	/// Offers `item` after the ranked suggestions, whatever was typed.
+	pub fn add_suggestion(&mut self, item: T) {
+		self.current_suggestions.push(Suggestion {
+			item,
+			positions: vec![],
+		});
+		if self.current_selection.is_none() {
+			self.select_suggestion(Some(0));
+		}
 	}
 
-	pub fn on_backspace(&mut self) {
-		if self.text.len() > 0 {
-			self.text.pop();
+	pub fn on_text_event(&mut self, input: impl Into<TextInput>) {
+		self.buffer.attach((0, 0), &self.text);
+		if let KeyResult::Edited(edit, _) = self.buffer.on_input(&self.text, input) {
+			edit.apply(&mut self.text);
+			self.filter();
 			self.broadcast_text();
+		}
+	}
+
+	pub fn on_backspace(&mut self, event: KeyEvent) {
+		if self.text.len() > 0 {
+			self.on_text_event(event);
 		} else {
 			if let Some(x) = &self.current_chosen {
 				if x.len() > 0 {
 					self.current_chosen = Some(String::from(""));
 					self.text = String::from("");
+					self.filter();
 					self.broadcast_text();
 				}
 			}
@@ -513,6 +1013,7 @@ where
 						self.current_suggestions
 							.get_mut(x)
 							.unwrap()
+							.item
 							.get_identifier(),
 					)))
 					.unwrap_or_default();
@@ -528,13 +1029,17 @@ where
 	pub fn set_text_to_choice(&mut self) {
 		match self.current_selection {
 			Some(x) => {
-				self.text = self.current_suggestions.get_mut(x).unwrap().get_name();
+				self.text = self.current_suggestions.get_mut(x).unwrap().item.get_name();
+				self.buffer.move_end_of_text(&self.text);
 			}
 			_ => {}
 		}
 	}
 
 	pub fn on_up(&mut self) {
+		if self.current_suggestions.is_empty() {
+			return;
+		}
 		self.select_suggestion(match self.current_selection {
 			Some(x) if x > 0 => Some(x - 1),
 			_ => Some(0),
@@ -543,16 +1048,39 @@ where
 	}
 	pub fn on_down(&mut self) {
 		self.select_suggestion(match self.current_selection {
-			Some(x) if x < self.current_suggestions.len() - 1 => Some(x + 1),
+			Some(x) if x + 1 < self.current_suggestions.len() => Some(x + 1),
 			Some(x) => Some(x),
 			None => None,
 		});
 		self.set_text_to_choice();
 	}
+	/// Selects the suggestion `rows` below the selected one, or above it
+	/// when negative, stopping at the first and the last.
+	fn move_by(&mut self, rows: isize) {
+		if self.current_suggestions.is_empty() {
+			return;
+		}
+		let position = self.current_selection.unwrap_or(0) as isize;
+		let last = self.current_suggestions.len() as isize - 1;
+		self.select_suggestion(Some((position + rows).min(last).max(0) as usize));
+		self.set_text_to_choice();
+	}
+	pub fn on_page_up(&mut self) {
+		self.move_by(-(self.viewport.page() as isize));
+	}
+	pub fn on_page_down(&mut self) {
+		self.move_by(self.viewport.page() as isize);
+	}
+	pub fn on_first(&mut self) {
+		self.move_by(-(self.current_suggestions.len() as isize));
+	}
+	pub fn on_last(&mut self) {
+		self.move_by(self.current_suggestions.len() as isize);
+	}
 	pub fn on_enter(&mut self) {
 		match self.current_selection {
 			Some(x) => {
-				self.text = self.current_suggestions.get_mut(x).unwrap().get_name();
+				self.text = self.current_suggestions.get_mut(x).unwrap().item.get_name();
 			}
 			None => {}
 		}
@@ -560,52 +1088,91 @@ where
 	}
 }
 
+/// The text box and the suggestions below it.
+fn autocomplete_chunks(area: Rect) -> Vec<Rect> {
+	Layout::default()
+		.constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
+		.margin(1)
+		.split(area)
+}
+
 impl<T> UIComponent for AutoCompleteEditor<T>
 where
 	T: SelectableItem,
 {
-	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect)
+	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
 	where
 		B: Backend,
 	{
+		self.area = area;
+		let style = match self.active {
+			true => theme.title,
+			false => Style::default(),
+		};
 		Block::default()
 			.borders(Borders::ALL)
 			.title(&self.title)
+			.title_style(style)
 			.render(f, area);
-		let chunks = Layout::default()
-			.constraints([Constraint::Length(3), Constraint::Min(1)].as_ref())
-			.margin(1)
-			.split(area);
-		let items: Vec<_> = self
-			.current_suggestions
-			.iter_mut()
-			.map(|x| (x.get_name()))
-			.collect();
-		let text = [Text::raw(self.text.clone())];
+		let chunks = autocomplete_chunks(area);
+		self.buffer.attach((0, 0), &self.text);
+		let text = match self.active {
+			true => self.buffer.spans(&self.text, Style::default()),
+			false => vec![Text::raw(self.text.clone())],
+		};
 
 		Paragraph::new(text.iter())
-			.block(
-				Block::default()
-					.borders(Borders::ALL)
-					.title("Find ...")
-					.title_style(Style::default()),
-			)
+			.block(Block::default().borders(Borders::ALL).title("Find ..."))
 			.wrap(true)
 			.render(f, chunks[0]);
 
-		SelectableList::default()
+		let selected_style = theme.selection;
+		let matched_style = theme.accent.modifier(theme.accent.modifier | Modifier::BOLD);
+		let selected_matched_style =
+			selected_style.modifier(selected_style.modifier | Modifier::BOLD | Modifier::UNDERLINED);
+		let mut lines = vec![];
+		for (i, suggestion) in self.current_suggestions.iter_mut().enumerate() {
+			let selected = self.current_selection == Some(i);
+			let (style, matched) = match selected {
+				true => (selected_style, selected_matched_style),
+				false => (Style::default(), matched_style),
+			};
+			lines.push(Text::styled(if selected { "> " } else { "  " }, style));
+			// Split the name into runs of matched and unmatched characters.
+			let mut run = String::new();
+			let mut run_matched = false;
+			for (j, c) in suggestion.item.get_name().chars().enumerate() {
+				let is_matched = suggestion.positions.contains(&j);
+				if is_matched != run_matched && !run.is_empty() {
+					let run_style = if run_matched { matched } else { style };
+					lines.push(Text::styled(std::mem::take(&mut run), run_style));
+				}
+				run_matched = is_matched;
+				run.push(c);
+			}
+			run.push('\n');
+			lines.push(Text::styled(run, if run_matched { matched } else { style }));
+		}
+		let height = chunks[1].height.saturating_sub(2) as usize;
+		let len = self.current_suggestions.len();
+		let selected = self.current_selection.map(|s| (s, s + 1));
+		self.viewport.follow(height, len, selected);
+		Paragraph::new(lines.iter())
 			.block(Block::default().borders(Borders::ALL).title("Options"))
-			.items(&items)
-			.select(self.current_selection)
-			.highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
-			.highlight_symbol(">")
+			.scroll(self.viewport.offset as u16)
 			.render(f, chunks[1]);
+		Scrollbar {
+			viewport: self.viewport,
+			len,
+			style: theme.accent,
+		}
+		.render(f, inner_rows(chunks[1], 0));
 	}
 }
 
 impl<T> UIEventProcessor for AutoCompleteEditor<T>
 where
-	T: SelectableItem,
+	T: SelectableItem + Clone,
 {
 	fn on_deactivate(&mut self) {
 		self.active = false
@@ -616,13 +1183,139 @@ where
 	fn on_event(&mut self, event: KeyEvent) {
 		if self.active {
 			match (event.code, event.modifiers) {
-				(KeyCode::Char(c), _) => self.on_key(c, event.modifiers),
-				(KeyCode::Backspace, _) => self.on_backspace(),
-				(KeyCode::Up, _) => self.on_up(),
-				(KeyCode::Down, _) => self.on_down(),
+				(KeyCode::Backspace, m) if m.is_empty() => self.on_backspace(event),
+				(_, _) => self.on_text_event(event),
+			}
+		}
+	}
+	/// Clicking a suggestion selects it, clicking it again picks it.
+	fn on_mouse(&mut self, event: MouseEvent) {
+		match event {
+			MouseEvent::Down(MouseButton::Left, column, row, _) => {
+				let options = autocomplete_chunks(self.area)[1];
+				match clicked_line(options, 0, &self.viewport, column, row) {
+					Some(line) if self.current_selection == Some(line) => self.on_enter(),
+					Some(line) if line < self.current_suggestions.len() => {
+						self.select_suggestion(Some(line));
+						self.set_text_to_choice();
+					}
+					_ => {}
+				}
+			}
+			MouseEvent::ScrollUp(..) => self.move_by(-(WHEEL_ROWS as isize)),
+			MouseEvent::ScrollDown(..) => self.move_by(WHEEL_ROWS as isize),
+			_ => {}
+		}
+	}
+	fn on_action(&mut self, action: Action) {
+		if self.active {
+			match action {
+				Action::Up => self.on_up(),
+				Action::Down => self.on_down(),
+				Action::PageUp => self.on_page_up(),
+				Action::PageDown => self.on_page_down(),
+				Action::First => self.on_first(),
+				Action::Last => self.on_last(),
+				Action::Choose => self.on_enter(),
+				action if action.edits_text() => self.on_text_event(action),
+				_ => {}
+			}
+		}
+	}
+}
+
+/// A single line of text, e.g. a query. Every change is broadcast as
+/// `ActionPayload::Text`, enter broadcasts the text as a `TextSelection`.
+pub struct LineEditor {
+	pub active: bool,
+	pub title: String,
+	pub text: String,
+	pub buffer: TextBuffer,
+	/// Where it was last drawn, for the mouse.
+	pub area: Rect,
+	pub sender: Sender<Event>,
+}
+
+impl LineEditor {
+	pub fn new(title: String, sender: Sender<Event>) -> LineEditor {
+		LineEditor {
+			active: false,
+			title,
+			text: String::new(),
+			buffer: TextBuffer::new(),
+			area: Rect::default(),
+			sender,
+		}
+	}
+
+	pub fn on_text_event(&mut self, input: impl Into<TextInput>) {
+		self.buffer.attach((0, 0), &self.text);
+		if let KeyResult::Edited(edit, _) = self.buffer.on_input(&self.text, input) {
+			edit.apply(&mut self.text);
+			self.broadcast_text();
+		}
+	}
+
+	pub fn broadcast_text(&mut self) {
+		self.sender
+			.send(Event::Action(ActionPayload::Text(self.text.clone())))
+			.unwrap_or_default();
+	}
+
+	pub fn on_enter(&mut self) {
+		self.sender
+			.send(Event::Action(ActionPayload::TextSelection(
+				self.title.clone(),
+				self.text.clone(),
+			)))
+			.unwrap_or_default();
+	}
+}
+
+impl UIComponent for LineEditor {
+	fn draw<B>(&mut self, f: &mut Frame<B>, area: Rect, theme: &Theme)
+	where
+		B: Backend,
+	{
+		self.area = area;
+		let style = match self.active {
+			true => theme.title,
+			false => Style::default(),
+		};
+		self.buffer.attach((0, 0), &self.text);
+		let text = match self.active {
+			true => self.buffer.spans(&self.text, Style::default()),
+			false => vec![Text::raw(self.text.clone())],
+		};
+		Paragraph::new(text.iter())
+			.block(
+				Block::default()
+					.borders(Borders::ALL)
+					.title(&self.title)
+					.title_style(style),
+			)
+			.render(f, area);
+	}
+}
+
+impl UIEventProcessor for LineEditor {
+	fn on_deactivate(&mut self) {
+		self.active = false
+	}
+	fn on_activate(&mut self) {
+		self.active = true;
+	}
+	fn on_event(&mut self, event: KeyEvent) {
+		if self.active {
+			match (event.code, event.modifiers) {
 				(KeyCode::Enter, _) => self.on_enter(),
-				(_, _) => {}
+				(_, _) => self.on_text_event(event),
 			}
 		}
 	}
+	fn on_action(&mut self, action: Action) {
+		if self.active && action.edits_text() {
+			self.on_text_event(action);
+		}
+	}
 }
@@ -0,0 +1,89 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::Widget;
+
+/// How many rows a notch of the mouse wheel moves.
+pub const WHEEL_ROWS: usize = 3;
+
+/// The lines of a list that a pane shows. It only moves as far as it takes
+/// to keep the selection in view, so moving up and down doesn't jump.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Viewport {
+    /// The first line shown.
+    pub offset: usize,
+    /// The number of lines shown, as of the last draw.
+    pub height: usize,
+}
+
+impl Viewport {
+    /// How far paging moves, never nothing.
+    pub fn page(&self) -> usize {
+        self.height.max(1)
+    }
+
+    /// Shows `height` of `len` lines, scrolling as little as possible to
+    /// show the `selected` lines, from the first to past the last. Lines
+    /// that don't fit at the top win over ones that don't at the bottom.
+    pub fn follow(&mut self, height: usize, len: usize, selected: Option<(usize, usize)>) {
+        self.height = height;
+        self.offset = self.offset.min(len.saturating_sub(height));
+        if let Some((start, end)) = selected {
+            if end > self.offset + height {
+                self.offset = end.saturating_sub(height);
+            }
+            if start < self.offset {
+                self.offset = start;
+            }
+        }
+    }
+
+    /// The line at `row` of the pane, if one is shown there.
+    pub fn line_at(&self, row: usize) -> Option<usize> {
+        match row < self.height {
+            true => Some(self.offset + row),
+            false => None,
+        }
+    }
+}
+
+/// The part of a list in view, drawn over the right border of its pane
+/// when the list doesn't fit.
+pub struct Scrollbar {
+    pub viewport: Viewport,
+    /// The lines of the whole list.
+    pub len: usize,
+    pub style: Style,
+}
+
+impl Scrollbar {
+    /// Where the thumb starts and how long it is, on a track `track` long.
+    pub fn thumb(&self, track: usize) -> Option<(usize, usize)> {
+        let height = self.viewport.height;
+        if self.len <= height || track == 0 {
+            return None;
+        }
+        let length = (track * height / self.len).max(1).min(track);
+        let scrolled = self.len - height;
+        let start = (track - length) * self.viewport.offset.min(scrolled) / scrolled;
+        Some((start, length))
+    }
+}
+
+impl Widget for Scrollbar {
+    /// Draws on the rightmost column of `area`, which should span the
+    /// lines of the list.
+    fn draw(&mut self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 {
+            return;
+        }
+        if let Some((start, length)) = self.thumb(area.height as usize) {
+            let x = area.right() - 1;
+            for y in start..start + length {
+                buf.get_mut(x, area.top() + y as u16)
+                    .set_symbol("█")
+                    .set_style(self.style);
+            }
+        }
+    }
+}
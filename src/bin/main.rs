@@ -1,40 +1,337 @@
 use code_pal::app;
 
 use std::{
-    io::{stdout, Write},
-    sync::mpsc,
+    env, fs,
+    io::{self, stdout, Stdout, Write},
+    panic, process,
+    sync::atomic::{AtomicBool, Ordering},
+    sync::{mpsc, Arc, Condvar, Mutex},
     thread,
     time::Duration,
 };
 
 use crossterm::{
-    event::{self, Event as CEvent, KeyCode, KeyModifiers},
+    cursor::{Hide, Show},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event as CEvent},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen},
 };
 
 use structopt::StructOpt;
-use tui::{backend::CrosstermBackend, Terminal};
+use tui::{backend::CrosstermBackend, layout::Rect, Terminal};
 
+use crate::app::external::{self, ExternalEdit};
+use crate::app::keymap::Keymap;
+use crate::app::modal::Modal;
+use crate::app::theme::Theme;
 use crate::app::{ui, App, AppState, Event};
+use code_pal::config::Config;
+use code_pal::storage::{
+    CorruptStore, JsonStore, Recovery, SqliteStore, Storage, StorageKind, Workspaces,
+    DEFAULT_WORKSPACE,
+};
 use crossterm::terminal::LeaveAlternateScreen;
+use std::path::PathBuf;
 
 #[derive(Debug, StructOpt)]
 struct Cli {
     #[structopt(long = "tick-rate", default_value = "250")]
     tick_rate: u64,
+    /// Milliseconds between ticks while there is nothing to autosave, no message to clear and no repository to poll
+    #[structopt(long = "idle-tick-rate", default_value = "10000")]
+    idle_tick_rate: u64,
     #[structopt(long = "log")]
     log: bool,
+    /// Store to use instead of the workspace's file in the data directory
+    #[structopt(long = "data-file", parse(from_os_str), conflicts_with = "workspace")]
+    data_file: Option<PathBuf>,
+    /// Named workspace to open, each workspace has its own store
+    #[structopt(long = "workspace", short = "w", default_value = DEFAULT_WORKSPACE)]
+    workspace: String,
+    /// Seconds to wait after the last edit before saving automatically, 0 disables autosave
+    #[structopt(long = "autosave-delay", default_value = "2")]
+    autosave_delay: u64,
+    /// How the store is kept on disk: json (one file, with backups) or sqlite
+    #[structopt(long = "storage", default_value = "json")]
+    storage: StorageKind,
+    /// Number of timestamped backups of the store to keep
+    #[structopt(long = "backups", default_value = "5")]
+    backups: usize,
+    /// Config file to use instead of config.toml in the config directory
+    #[structopt(long = "config", parse(from_os_str))]
+    config: Option<PathBuf>,
+    /// Color scheme: dark, light, solarized, high-contrast or monochrome
+    #[structopt(long = "theme")]
+    theme: Option<String>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Lists the available backups, or rolls the store back to one of them
+    Restore {
+        /// Index (as printed by `restore` without arguments) or path of the backup
+        backup: Option<String>,
+    },
+}
+
+fn restore(store: &JsonStore, backup: Option<&str>) -> Result<(), failure::Error> {
+    let backups = store.backups()?;
+    match backup {
+        None => {
+            if backups.is_empty() {
+                println!("No backups of {} found", store.path.display());
+            }
+            for (i, path) in backups.iter().enumerate() {
+                println!("{}: {}", i, path.display());
+            }
+        }
+        Some(choice) => {
+            let path = match choice.parse::<usize>() {
+                Ok(index) => backups
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| failure::format_err!("there is no backup {}", index))?,
+                Err(_) => PathBuf::from(choice),
+            };
+            let json = fs::read_to_string(&path)?;
+            AppState::from_json(&path, &json)?;
+            store.restore(&path)?;
+            println!("Restored {} from {}", store.path.display(), path.display());
+        }
+    }
+    Ok(())
+}
+
+/// Loads the store, asking on the terminal what to do when it turns out to be
+/// corrupt. Returns `None` when the user chooses to quit instead.
+fn open_store(store: &mut JsonStore) -> Result<Option<AppState>, failure::Error> {
+    let error = match store.load() {
+        Ok(app_state) => return Ok(Some(app_state)),
+        Err(error) => error,
+    };
+    match error.downcast_ref::<CorruptStore>() {
+        Some(corrupt) => eprintln!("{}", corrupt),
+        None => return Err(error),
+    }
+
+    let backup = store.last_good_backup();
+    match &backup {
+        Some(path) => eprintln!("  [b] open the last good backup ({})", path.display()),
+        None => eprintln!("  (there is no usable backup)"),
+    }
+    eprintln!("  [f] start a fresh store");
+    eprintln!("  [q] quit");
+    loop {
+        eprint!("> ");
+        io::stderr().flush()?;
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(None);
+        }
+        let recovery = match (answer.trim(), &backup) {
+            ("b", Some(backup)) => Recovery::Backup(backup.clone()),
+            ("f", _) => Recovery::Fresh,
+            ("q", _) => return Ok(None),
+            _ => continue,
+        };
+        let (app_state, quarantined) = store.recover(&recovery)?;
+        eprintln!("Moved the corrupt store to {}", quarantined.display());
+        return Ok(Some(app_state));
+    }
+}
+
+/// The config file, where it is, and the keymap and theme made from it.
+struct Settings {
+    path: PathBuf,
+    config: Config,
+    keymap: Keymap,
+    theme: Theme,
+}
+
+/// The settings, which are checked before the ui starts.
+fn load_config(cli: &Cli) -> Result<Settings, failure::Error> {
+    let path = match &cli.config {
+        Some(path) => path.clone(),
+        None => Config::default_path()?,
+    };
+    let config = Config::load(&path)?;
+    let keymap = Keymap::from_config(&config.keys)
+        .map_err(|e| failure::format_err!("{}: {}", path.display(), e))?;
+    // https://no-color.org: set and not empty.
+    let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    let theme = Theme::from_config(&config.theme, cli.theme.as_deref(), no_color)
+        .map_err(|e| failure::format_err!("{}: {}", path.display(), e))?;
+    config
+        .layout
+        .validate()
+        .map_err(|e| failure::format_err!("{}: {}", path.display(), e))?;
+    Ok(Settings {
+        path,
+        config,
+        keymap,
+        theme,
+    })
+}
+
+fn restore_terminal() -> Result<(), failure::Error> {
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)?;
+    Ok(())
+}
+
+/// Stops the input thread from reading the terminal while another program
+/// uses it. Polling for events already takes input off the terminal, so
+/// `pause` waits until the thread is idle.
+#[derive(Clone, Default)]
+struct InputGate {
+    paused: Arc<AtomicBool>,
+    idle: Arc<AtomicBool>,
+}
+
+impl InputGate {
+    fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        while !self.idle.load(Ordering::SeqCst) {
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn resume(&self) {
+        self.idle.store(false, Ordering::SeqCst);
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Whether the thread should keep off the terminal, marking it idle if
+    /// so.
+    fn is_paused(&self) -> bool {
+        let paused = self.paused.load(Ordering::SeqCst);
+        if paused {
+            self.idle.store(true, Ordering::SeqCst);
+        }
+        paused
+    }
+}
+
+/// Sends the main loop a tick every `tick_rate`, or every `idle_tick_rate`
+/// while the app has nothing to do on ticks. Leaving the idle state wakes it
+/// up at once, so ticks don't wait for the end of a long idle wait.
+#[derive(Default)]
+struct Ticker {
+    idle: Mutex<bool>,
+    changed: Condvar,
+}
+
+impl Ticker {
+    fn set_idle(&self, idle: bool) {
+        let mut current = self.idle.lock().unwrap();
+        if *current != idle {
+            *current = idle;
+            self.changed.notify_one();
+        }
+    }
+
+    fn run(&self, sender: mpsc::Sender<Event>, tick_rate: Duration, idle_tick_rate: Duration) {
+        let mut idle = self.idle.lock().unwrap();
+        loop {
+            let was_idle = *idle;
+            let rate = match was_idle {
+                true => idle_tick_rate,
+                false => tick_rate,
+            };
+            let (guard, wait) = self
+                .changed
+                .wait_timeout_while(idle, rate, |idle| *idle == was_idle)
+                .unwrap();
+            idle = guard;
+            if wait.timed_out() && sender.send(Event::Tick).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Hands the terminal over to `$EDITOR` for `edit` and takes it back.
+fn edit_externally(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    input: &InputGate,
+    edit: &ExternalEdit,
+) -> Result<Result<String, failure::Error>, failure::Error> {
+    input.pause();
+    restore_terminal()?;
+    let result = external::edit(&edit.content);
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen, EnableMouseCapture, Hide)?;
+    // Resizing forgets what was drawn before, so the next draw is complete.
+    let size = terminal.size()?;
+    terminal.resize(size)?;
+    input.resume();
+    Ok(result)
 }
 
 fn main() -> Result<(), failure::Error> {
     let cli = Cli::from_args();
     stderrlog::new().quiet(!cli.log).verbosity(4).init()?;
 
+    let settings = match load_config(&cli) {
+        Ok(loaded) => loaded,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+
+    let workspaces = Workspaces::new(Workspaces::default_root()?, cli.storage, cli.backups);
+    let path = match &cli.data_file {
+        Some(path) => path.clone(),
+        None => workspaces.path(&cli.workspace)?,
+    };
+
+    if let Some(Command::Restore { backup }) = &cli.command {
+        if cli.storage != StorageKind::Json {
+            failure::bail!("backups are only kept for the json storage");
+        }
+        return restore(&JsonStore::new(path, cli.backups), backup.as_deref());
+    }
+
+    let (store, app_state): (Box<dyn Storage>, AppState) = match cli.storage {
+        StorageKind::Json => {
+            let mut store = JsonStore::new(path, cli.backups);
+            match open_store(&mut store)? {
+                Some(app_state) => (Box::new(store), app_state),
+                None => return Ok(()),
+            }
+        }
+        StorageKind::Sqlite => {
+            let mut store = SqliteStore::open(&path)?;
+            let app_state = store.load()?;
+            (Box::new(store), app_state)
+        }
+    };
+
+    // Leave the terminal usable whatever happens from here on.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        restore_terminal().unwrap_or_default();
+        default_hook(info);
+    }));
+
     enable_raw_mode()?;
+    let result = run(&cli, settings, app_state, store, workspaces);
+    restore_terminal()?;
+    result
+}
 
+fn run(
+    cli: &Cli,
+    settings: Settings,
+    app_state: AppState,
+    store: Box<dyn Storage>,
+    workspaces: Workspaces,
+) -> Result<(), failure::Error> {
     let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
 
     let backend = CrosstermBackend::new(stdout);
 
@@ -46,48 +343,89 @@ fn main() -> Result<(), failure::Error> {
 
     let tx1 = mpsc::Sender::clone(&tx);
     let tx2 = mpsc::Sender::clone(&tx);
+    let tx3 = mpsc::Sender::clone(&tx);
 
-    thread::spawn(move || {
-        loop {
-            // poll for tick rate duration, if no events, sent tick event.
-            if let Ok(_) = event::poll(Duration::from_millis(cli.tick_rate)) {
-                if let Ok(CEvent::Key(key)) = event::read() {
-                    tx1.send(Event::Input(key)).unwrap_or_default();
-                }
-            }
-
-            tx1.send(Event::Tick).unwrap_or_default();
+    let tick_rate = Duration::from_millis(cli.tick_rate);
+    let idle_tick_rate = Duration::from_millis(cli.idle_tick_rate);
+    let input = InputGate::default();
+    let gate = input.clone();
+    thread::spawn(move || loop {
+        if gate.is_paused() {
+            thread::sleep(Duration::from_millis(5));
+            continue;
+        }
+        // Come back now and then to see whether the terminal is handed over.
+        if let Ok(true) = event::poll(tick_rate) {
+            let event = match event::read() {
+                Ok(CEvent::Key(key)) => Event::Input(key),
+                Ok(CEvent::Mouse(mouse)) => Event::Mouse(mouse),
+                Ok(CEvent::Resize(width, height)) => Event::Resize(width, height),
+                Err(_) => continue,
+            };
+            tx1.send(event).unwrap_or_default();
         }
     });
 
-    let app_state = AppState::new();
-    let mut app = App::new("Code Pal", app_state, tx2);
+    let ticker = Arc::new(Ticker::default());
+    let ticks = ticker.clone();
+    thread::spawn(move || ticks.run(tx3, tick_rate, idle_tick_rate));
+
+    let mut app = App::new(
+        "Code Pal",
+        app_state,
+        store,
+        workspaces,
+        cli.workspace.clone(),
+        tx2,
+    );
+    app.keymap = settings.keymap;
+    app.theme = settings.theme;
+    app.set_layout(settings.config.layout);
+    app.config_path = Some(settings.path);
+    if settings.config.editing.modal {
+        app.modal = Some(Modal::default());
+    }
+    app.autosave_delay = match cli.autosave_delay {
+        0 => None,
+        seconds => Some(Duration::from_secs(seconds)),
+    };
 
     terminal.clear()?;
+    ui::draw(&mut terminal, &mut app)?;
 
     loop {
-        ui::draw(&mut terminal, &mut app)?;
-        match rx.recv()? {
-            Event::Input(event) => match (event.code, event.modifiers) {
-                (KeyCode::Char('q'), KeyModifiers::CONTROL) => {
-                    disable_raw_mode()?;
-                    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-                    terminal.show_cursor()?;
-                    break;
-                }
-                (_, _) => app.on_key(event),
-            },
-            Event::Tick => {
-                app.on_tick();
+        // Only draw again when something may look different.
+        let mut redraw = match rx.recv()? {
+            Event::Input(event) => {
+                app.on_key(event);
+                true
             }
-
+            Event::Mouse(event) => {
+                app.on_mouse(event);
+                true
+            }
+            Event::Resize(width, height) => {
+                terminal.resize(Rect::new(0, 0, width, height))?;
+                true
+            }
+            Event::Tick => app.on_tick(),
             Event::Action(action) => {
                 app.on_action(action);
+                true
             }
+        };
+        if let Some(edit) = app.external_edit.take() {
+            let result = edit_externally(&mut terminal, &input, &edit)?;
+            app.on_external_edit(edit, result);
+            redraw = true;
         }
         if app.should_quit {
             break;
         }
+        ticker.set_idle(app.is_idle());
+        if redraw {
+            ui::draw(&mut terminal, &mut app)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,176 @@
+use crate::app::layout::ColumnWidth;
+use crate::storage::json::write_atomic;
+use failure::{format_err, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, Item};
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+/// Settings from `config.toml`, everything in it is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub keys: KeysConfig,
+    pub editing: EditingConfig,
+    pub theme: ThemeConfig,
+    pub layout: LayoutConfig,
+}
+
+/// The `[editing]` table.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EditingConfig {
+    /// Vim-like normal and insert modes in the todo list and the notes.
+    pub modal: bool,
+}
+
+/// The `[theme]` table: a palette and the colors to change in it, e.g.
+///
+/// ```toml
+/// [theme]
+/// name = "solarized"
+///
+/// [theme.colors]
+/// selection = "#d33682"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    pub name: Option<String>,
+    pub colors: BTreeMap<String, String>,
+}
+
+/// The `[layout]` table, which is also written when the panes are resized.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LayoutConfig {
+    /// Percent of the width of the notes tab taken by the todo list and the
+    /// notes.
+    pub list_width: u16,
+    /// Percent of the height of the notes tab taken by the todo list.
+    pub todo_height: u16,
+    /// Percent of the height beside the notes taken by the description.
+    pub description_height: u16,
+    /// Panes are stacked instead of side by side below this many columns.
+    pub stack_below: u16,
+    pub project_columns: Vec<ColumnWidth>,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> LayoutConfig {
+        LayoutConfig {
+            list_width: 35,
+            todo_height: 30,
+            description_height: 40,
+            stack_below: 80,
+            project_columns: vec![
+                ColumnWidth::Auto,
+                ColumnWidth::Percentage(40),
+                ColumnWidth::Percentage(40),
+            ],
+        }
+    }
+}
+
+/// The `[keys]` table: a preset to start from and, per context, the keys
+/// of the actions to bind differently, e.g.
+///
+/// ```toml
+/// [keys]
+/// preset = "emacs"
+///
+/// [keys.todo_list]
+/// down = ["down", "alt+j"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    pub preset: Option<String>,
+    #[serde(flatten)]
+    pub contexts: BTreeMap<String, BTreeMap<String, Keys>>,
+}
+
+/// One key or several, an empty list unbinds the action.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Keys {
+    pub fn iter(&self) -> impl Iterator<Item = &String> {
+        match self {
+            Keys::One(key) => std::slice::from_ref(key).iter(),
+            Keys::Many(keys) => keys.iter(),
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/code_pal/config.toml` or the platform equivalent.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        dirs::config_dir()
+            .map(|dir| dir.join("code_pal").join(CONFIG_FILE_NAME))
+            .ok_or_else(|| format_err!("couldn't determine the config directory"))
+    }
+
+    pub fn parse(toml: &str) -> Result<Config, Error> {
+        toml::from_str(toml).map_err(Error::from)
+    }
+
+    /// Reads the config at `path`, a missing file being an empty config.
+    pub fn load(path: &Path) -> Result<Config, Error> {
+        match fs::read_to_string(path) {
+            Ok(toml) => Config::parse(&toml)
+                .map_err(|e| format_err!("{} is invalid: {}", path.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(format_err!("couldn't read {}: {}", path.display(), e)),
+        }
+    }
+
+    /// Writes `layout` as the `[layout]` table of the config at `path`,
+    /// leaving the rest of the file and the comments in it as they are.
+    pub fn save_layout(path: &Path, layout: &LayoutConfig) -> Result<(), Error> {
+        let toml = match fs::read_to_string(path) {
+            Ok(toml) => toml,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format_err!("couldn't read {}: {}", path.display(), e)),
+        };
+        let mut document: DocumentMut = toml
+            .parse()
+            .map_err(|e| format_err!("{} is invalid: {}", path.display(), e))?;
+        let values: DocumentMut = toml::to_string(layout)?.parse()?;
+        // `[layout]`, `layout = { .. }` or `layout.` keys, whichever is there.
+        let layout_item = document.entry("layout").or_insert_with(toml_edit::table);
+        let table = layout_item
+            .as_table_like_mut()
+            .ok_or_else(|| format_err!("layout in {} isn't a table", path.display()))?;
+        for (key, value) in values.iter() {
+            match (table.get_mut(key), value.as_value()) {
+                (Some(Item::Value(old)), Some(value)) => {
+                    // The comments and spacing around the old value stay.
+                    let decor = old.decor().clone();
+                    *old = value.clone();
+                    *old.decor_mut() = decor;
+                }
+                _ => {
+                    table.insert(key, value.clone());
+                }
+            }
+        }
+        // Inline tables have no comments to keep, only the spacing to fix.
+        if let Some(inline) = layout_item.as_inline_table_mut() {
+            inline.fmt();
+        }
+        let toml = document.to_string();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        write_atomic(path, toml.as_bytes())
+    }
+}
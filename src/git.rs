@@ -0,0 +1,194 @@
+use chrono::{DateTime, TimeZone, Utc};
+use failure::{bail, format_err, Error};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A commit as `git log` shows it on one line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub summary: String,
+    pub time: DateTime<Utc>,
+}
+
+impl Commit {
+    /// How long ago the commit was made, roughly, e.g. `3 hours ago`.
+    pub fn age(&self, now: DateTime<Utc>) -> String {
+        let seconds = (now - self.time).num_seconds().max(0);
+        let (count, unit) = match seconds {
+            s if s < 60 => return String::from("just now"),
+            s if s < 3600 => (s / 60, "minute"),
+            s if s < 86400 => (s / 3600, "hour"),
+            s if s < 86400 * 30 => (s / 86400, "day"),
+            s if s < 86400 * 365 => (s / (86400 * 30), "month"),
+            s => (s / (86400 * 365), "year"),
+        };
+        match count {
+            1 => format!("1 {} ago", unit),
+            count => format!("{} {}s ago", count, unit),
+        }
+    }
+}
+
+/// What a project's directory looks like as a git repository.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoStatus {
+    /// The branch checked out, `None` on a detached head.
+    pub branch: Option<String>,
+    /// Whether anything is changed, staged or untracked.
+    pub dirty: bool,
+    /// The commits ahead of and behind the upstream, if there is one.
+    pub ahead_behind: Option<(usize, usize)>,
+    /// `None` before the first commit.
+    pub last_commit: Option<Commit>,
+}
+
+/// `dir` with a leading `~` standing for the home directory.
+pub fn expand_home(dir: &str) -> PathBuf {
+    match (dir.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with('/') => {
+            home.join(rest.trim_start_matches('/'))
+        }
+        _ => PathBuf::from(dir),
+    }
+}
+
+/// Runs git in `dir`, failing with what git printed if it fails.
+pub fn git(dir: &Path, args: &[&str]) -> Result<String, Error> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format_err!("couldn't run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!("{}", stderr.lines().next().unwrap_or("git failed").trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The branch, whether the tree is dirty and how far it is from its
+/// upstream, read from `git status --porcelain=v2 --branch`.
+pub fn parse_status(output: &str) -> (Option<String>, bool, Option<(usize, usize)>) {
+    let mut branch = None;
+    let mut dirty = false;
+    let mut ahead_behind = None;
+    for line in output.lines() {
+        if let Some(head) = line.strip_prefix("# branch.head ") {
+            branch = Some(head.to_string()).filter(|head| head != "(detached)");
+        } else if let Some(counts) = line.strip_prefix("# branch.ab ") {
+            let mut counts = counts
+                .split(' ')
+                .map(|count| count.trim_start_matches(['+', '-']).parse());
+            if let (Some(Ok(ahead)), Some(Ok(behind))) = (counts.next(), counts.next()) {
+                ahead_behind = Some((ahead, behind));
+            }
+        } else if !line.starts_with('#') && !line.is_empty() {
+            dirty = true;
+        }
+    }
+    (branch, dirty, ahead_behind)
+}
+
+/// The format of `git log` that `parse_log` reads.
+pub const LOG_FORMAT: &str = "--format=%h%x00%ct%x00%s";
+
+/// The commits of `git log` run with `LOG_FORMAT`.
+pub fn parse_log(output: &str) -> Vec<Commit> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\0');
+            let hash = fields.next()?.to_string();
+            let time = Utc
+                .timestamp_opt(fields.next()?.parse().ok()?, 0)
+                .single()?;
+            let summary = fields.next()?.to_string();
+            Some(Commit {
+                hash,
+                summary,
+                time,
+            })
+        })
+        .collect()
+}
+
+/// Inspects the repository in `dir`, which fails if it isn't one. It's
+/// polled, so it doesn't refresh the index: that would take its lock from
+/// under whatever git command the user runs meanwhile.
+pub fn status(dir: &Path) -> Result<RepoStatus, Error> {
+    let output = git(
+        dir,
+        &[
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--branch",
+        ],
+    )?;
+    let (branch, dirty, ahead_behind) = parse_status(&output);
+    // Fails before the first commit, which just means there is none.
+    let last_commit = git(dir, &["log", "-1", LOG_FORMAT])
+        .ok()
+        .and_then(|log| parse_log(&log).into_iter().next());
+    Ok(RepoStatus {
+        branch,
+        dirty,
+        ahead_behind,
+        last_commit,
+    })
+}
+
+/// Whether git takes `name` as the name of a new branch, following the
+/// rules of `git check-ref-format --branch`.
+pub fn valid_branch_name(name: &str) -> bool {
+    const FORBIDDEN: &[char] = &[' ', '~', '^', ':', '?', '*', '[', '\\'];
+    name != "@"
+        && name != "HEAD"
+        && !name.starts_with('-')
+        && !name.ends_with('.')
+        && !name.contains("..")
+        && !name.contains("@{")
+        && !name
+            .chars()
+            .any(|c| c.is_ascii_control() || FORBIDDEN.contains(&c))
+        && name
+            .split('/')
+            .all(|part| !part.is_empty() && !part.starts_with('.') && !part.ends_with(".lock"))
+}
+
+/// Checks out `branch`, creating it at the current commit if it doesn't
+/// exist yet. Returns whether it was created.
+pub fn checkout(dir: &Path, branch: &str) -> Result<bool, Error> {
+    let created = !has_branch(dir, branch);
+    match created {
+        true => git(dir, &["checkout", "-q", "-b", branch])?,
+        false => git(dir, &["checkout", "-q", branch])?,
+    };
+    Ok(created)
+}
+
+/// Whether `branch` is a local branch of the repository in `dir`.
+pub fn has_branch(dir: &Path, branch: &str) -> bool {
+    let head = format!("refs/heads/{}", branch);
+    git(dir, &["rev-parse", "--verify", "--quiet", &head]).is_ok()
+}
+
+/// The commits on `branch` that no other local branch has, newest first,
+/// or `None` if there is no such branch yet.
+pub fn branch_commits(dir: &Path, branch: &str) -> Result<Option<Vec<Commit>>, Error> {
+    if !has_branch(dir, branch) {
+        // Also fails when `dir` isn't a repository, which should say so.
+        git(dir, &["rev-parse", "--git-dir"])?;
+        return Ok(None);
+    }
+    let head = format!("refs/heads/{}", branch);
+    // Patterns excluded from `--branches` leave out `refs/heads/`.
+    let others = format!("--exclude={}", branch);
+    let output = git(
+        dir,
+        &["log", LOG_FORMAT, &head, "--not", &others, "--branches"],
+    )?;
+    Ok(Some(parse_log(&output)))
+}
@@ -3,4 +3,7 @@ pub mod app;
 #[allow(dead_code)]
 pub mod util;
 
+pub mod config;
+pub mod git;
+pub mod storage;
 pub mod todo;
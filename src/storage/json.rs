@@ -0,0 +1,286 @@
+use crate::app::AppState;
+use crate::storage::Storage;
+use chrono::Local;
+use failure::{bail, format_err, Error};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::prelude::*;
+use std::path::{Path, PathBuf};
+
+const BACKUP_EXTENSION: &str = "bak";
+const QUARANTINE_EXTENSION: &str = "corrupt";
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S%.3f";
+
+/// Returned when a store exists but can't be parsed.
+#[derive(Debug)]
+pub struct CorruptStore {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl CorruptStore {
+    pub fn new(path: &Path, error: &serde_json::Error) -> CorruptStore {
+        let position = format!(" at line {} column {}", error.line(), error.column());
+        CorruptStore {
+            path: path.to_path_buf(),
+            line: error.line(),
+            column: error.column(),
+            message: error.to_string().trim_end_matches(&position).to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CorruptStore {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} is corrupt at line {}, column {}: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+impl std::error::Error for CorruptStore {}
+
+/// What to put in place of a store that turns out to be corrupt.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Recovery {
+    /// The contents of this backup.
+    Backup(PathBuf),
+    /// Nothing, to start again.
+    Fresh,
+}
+
+/// A JSON file on disk holding the whole `AppState`, together with the
+/// timestamped backups that are kept next to it.
+#[derive(Debug, Clone)]
+pub struct JsonStore {
+    pub path: PathBuf,
+    pub backup_count: usize,
+}
+
+impl JsonStore {
+    pub fn new(path: PathBuf, backup_count: usize) -> JsonStore {
+        JsonStore { path, backup_count }
+    }
+
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    pub fn read(&self) -> Result<String, Error> {
+        fs::read_to_string(&self.path)
+            .map_err(|e| format_err!("couldn't read {}: {}", self.path.display(), e))
+    }
+
+    /// Replaces the store with `contents` without ever leaving a truncated
+    /// file behind: the data is written and synced to a temporary file in the
+    /// same directory which is then renamed over the store. The previous
+    /// contents are kept as a backup.
+    pub fn write(&self, contents: &str) -> Result<(), Error> {
+        if self.exists() {
+            if self.read()? == contents {
+                return Ok(());
+            }
+            self.backup()?;
+        } else {
+            let dir = self.directory();
+            fs::create_dir_all(&dir)
+                .map_err(|e| format_err!("couldn't create {}: {}", dir.display(), e))?;
+        }
+        write_atomic(&self.path, contents.as_bytes())
+    }
+
+    /// Copies the current store to a new timestamped backup and removes the
+    /// oldest backups so that at most `backup_count` remain.
+    pub fn backup(&self) -> Result<Option<PathBuf>, Error> {
+        if self.backup_count == 0 || !self.exists() {
+            return Ok(None);
+        }
+        let backup_path = self.backup_path();
+        fs::copy(&self.path, &backup_path)
+            .map_err(|e| format_err!("couldn't create backup {}: {}", backup_path.display(), e))?;
+        self.prune_backups()?;
+        Ok(Some(backup_path))
+    }
+
+    /// Lists the backups of this store, newest first.
+    pub fn backups(&self) -> Result<Vec<PathBuf>, Error> {
+        let dir = self.directory();
+        if !dir.exists() {
+            return Ok(vec![]);
+        }
+        let prefix = self.backup_prefix();
+        let mut backups: Vec<PathBuf> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension().and_then(|e| e.to_str()) == Some(BACKUP_EXTENSION)
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.starts_with(&prefix))
+            })
+            .collect();
+        backups.sort();
+        backups.reverse();
+        Ok(backups)
+    }
+
+    /// Replaces the store with the contents of `backup`. The current store is
+    /// backed up first so that a restore can itself be rolled back.
+    pub fn restore(&self, backup: &Path) -> Result<(), Error> {
+        if !backup.exists() {
+            bail!("backup {} does not exist", backup.display());
+        }
+        let contents = fs::read_to_string(backup)
+            .map_err(|e| format_err!("couldn't read {}: {}", backup.display(), e))?;
+        self.write(&contents)
+    }
+
+    /// Moves the store out of the way so a fresh or restored one can take its
+    /// place, keeping the broken file around for inspection.
+    pub fn quarantine(&self) -> Result<PathBuf, Error> {
+        let timestamp = Local::now().format(BACKUP_TIMESTAMP_FORMAT);
+        let quarantine_path = self.directory().join(format!(
+            "{}{}.{}",
+            self.backup_prefix(),
+            timestamp,
+            QUARANTINE_EXTENSION
+        ));
+        fs::rename(&self.path, &quarantine_path).map_err(|e| {
+            format_err!(
+                "couldn't move {} to {}: {}",
+                self.path.display(),
+                quarantine_path.display(),
+                e
+            )
+        })?;
+        Ok(quarantine_path)
+    }
+
+    /// The newest backup that can still be loaded, if any.
+    pub fn last_good_backup(&self) -> Option<PathBuf> {
+        self.backups().ok()?.into_iter().find(|path| {
+            fs::read_to_string(path)
+                .map(|json| AppState::from_json(path, &json).is_ok())
+                .unwrap_or(false)
+        })
+    }
+
+    /// Quarantines a corrupt store and loads what `recovery` puts in its
+    /// place. Returns the state and where the corrupt store was moved.
+    pub fn recover(&mut self, recovery: &Recovery) -> Result<(AppState, PathBuf), Error> {
+        let quarantined = self.quarantine()?;
+        let app_state = match recovery {
+            Recovery::Backup(backup) => {
+                self.restore(backup)?;
+                self.load()?
+            }
+            Recovery::Fresh => AppState::empty(),
+        };
+        Ok((app_state, quarantined))
+    }
+
+    fn prune_backups(&self) -> Result<(), Error> {
+        for old in self.backups()?.into_iter().skip(self.backup_count) {
+            fs::remove_file(&old)
+                .map_err(|e| format_err!("couldn't remove backup {}: {}", old.display(), e))?;
+        }
+        Ok(())
+    }
+
+    fn directory(&self) -> PathBuf {
+        match self.path.parent() {
+            Some(parent) if parent != Path::new("") => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        }
+    }
+
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn backup_prefix(&self) -> String {
+        format!("{}.", self.file_name())
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        let timestamp = Local::now().format(BACKUP_TIMESTAMP_FORMAT);
+        self.directory().join(format!(
+            "{}{}.{}",
+            self.backup_prefix(),
+            timestamp,
+            BACKUP_EXTENSION
+        ))
+    }
+}
+
+impl Storage for JsonStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&mut self) -> Result<AppState, Error> {
+        if self.exists() {
+            let json = self.read()?;
+            return AppState::from_json(&self.path, &json);
+        }
+        Ok(AppState::empty())
+    }
+
+    fn save(&mut self, state: &AppState) -> Result<(), Error> {
+        let serialized = serde_json::to_string(state)?;
+        self.write(&serialized)
+    }
+}
+
+/// Writes `contents` to a temporary sibling of `path`, flushes it to disk and
+/// renames it into place, so readers only ever see the old or the new file.
+pub fn write_atomic(path: &Path, contents: &[u8]) -> Result<(), Error> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format_err!("{} is not a file path", path.display()))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let result = (|| -> Result<(), Error> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&temp_path)
+            .map_err(|e| format_err!("couldn't create {}: {}", temp_path.display(), e))?;
+        file.write_all(contents)
+            .map_err(|e| format_err!("couldn't write to {}: {}", temp_path.display(), e))?;
+        file.sync_all()
+            .map_err(|e| format_err!("couldn't sync {}: {}", temp_path.display(), e))?;
+        fs::rename(&temp_path, path)
+            .map_err(|e| format_err!("couldn't replace {}: {}", path.display(), e))?;
+        Ok(())
+    })();
+
+    if result.is_err() {
+        fs::remove_file(&temp_path).unwrap_or_default();
+        return result;
+    }
+
+    // Make the rename itself durable; not every platform lets us open a
+    // directory, so failures here are not fatal.
+    if let Some(parent) = path.parent() {
+        if let Ok(dir) = File::open(parent) {
+            dir.sync_all().unwrap_or_default();
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,110 @@
+use chrono::Utc;
+use failure::{bail, format_err, Error};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+/// The version of the store layout written by this build. Bump it together
+/// with a new entry in `MIGRATIONS` whenever the persisted model changes.
+pub const SCHEMA_VERSION: u64 = 4;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), Error>;
+
+/// `MIGRATIONS[n]` upgrades a store from version `n` to version `n + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4];
+
+/// The schema version of a raw store, stores written before versioning was
+/// introduced count as version 0.
+pub fn version_of(state: &Value) -> Result<u64, Error> {
+    match state.get("schema_version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .ok_or_else(|| format_err!("schema_version must be a number, found {}", version)),
+    }
+}
+
+/// Upgrades a raw store step by step to `SCHEMA_VERSION`.
+pub fn migrate(mut state: Value) -> Result<Value, Error> {
+    let version = version_of(&state)?;
+    if version > SCHEMA_VERSION {
+        bail!(
+            "the store was written by a newer version of code_pal (schema {}, this build reads up to {})",
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    let object = state
+        .as_object_mut()
+        .ok_or_else(|| format_err!("the store must be a JSON object"))?;
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(object)
+            .map_err(|e| format_err!("couldn't upgrade the store from schema {}: {}", from, e))?;
+        object.insert(String::from("schema_version"), json!(from as u64 + 1));
+    }
+    Ok(state)
+}
+
+fn array_mut<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<&'a mut Vec<Value>, Error> {
+    object
+        .get_mut(key)
+        .and_then(|value| value.as_array_mut())
+        .ok_or_else(|| format_err!("{} must be a list", key))
+}
+
+fn objects_mut<'a>(
+    object: &'a mut Map<String, Value>,
+    key: &str,
+) -> Result<impl Iterator<Item = &'a mut Map<String, Value>>, Error> {
+    Ok(array_mut(object, key)?
+        .iter_mut()
+        .filter_map(|value| value.as_object_mut()))
+}
+
+/// Unversioned stores predate projects: todos may lack `project_identifier`
+/// and the state may lack `projects`, which starts with one empty row.
+fn v0_to_v1(state: &mut Map<String, Value>) -> Result<(), Error> {
+    for todo in objects_mut(state, "todo_items")? {
+        todo.entry("project_identifier").or_insert(json!(""));
+    }
+    state.entry("projects").or_insert_with(|| {
+        json!([{
+            "identifier": Uuid::new_v4().to_string(),
+            "name": "",
+            "url": "",
+            "dir_location": "",
+        }])
+    });
+    Ok(())
+}
+
+/// Version 2 adds a status, a priority and an optional due date to todos.
+fn v1_to_v2(state: &mut Map<String, Value>) -> Result<(), Error> {
+    for todo in objects_mut(state, "todo_items")? {
+        todo.entry("status").or_insert(json!("todo"));
+        todo.entry("priority").or_insert(json!("normal"));
+        todo.entry("due").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+/// Version 3 tracks when todos were created and last updated. Older todos get
+/// the time of the upgrade for both.
+fn v2_to_v3(state: &mut Map<String, Value>) -> Result<(), Error> {
+    let now = json!(Utc::now());
+    for todo in objects_mut(state, "todo_items")? {
+        todo.entry("created_at").or_insert_with(|| now.clone());
+        todo.entry("updated_at").or_insert_with(|| now.clone());
+    }
+    Ok(())
+}
+
+/// Version 4 lets todos name the git branch they are worked on.
+fn v3_to_v4(state: &mut Map<String, Value>) -> Result<(), Error> {
+    for todo in objects_mut(state, "todo_items")? {
+        todo.entry("branch").or_insert(Value::Null);
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+pub mod json;
+pub mod migrations;
+pub mod sqlite;
+pub mod workspace;
+pub use json::{CorruptStore, JsonStore, Recovery};
+pub use migrations::SCHEMA_VERSION;
+pub use sqlite::SqliteStore;
+pub use workspace::{Workspace, Workspaces, DEFAULT_WORKSPACE};
+
+use crate::app::AppState;
+use failure::{bail, Error};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Loads and saves the todos, notes and projects of a workspace.
+pub trait Storage {
+    fn path(&self) -> &Path;
+    fn load(&mut self) -> Result<AppState, Error>;
+    fn save(&mut self, state: &AppState) -> Result<(), Error>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StorageKind {
+    Json,
+    Sqlite,
+}
+
+impl StorageKind {
+    pub fn extension(self) -> &'static str {
+        match self {
+            StorageKind::Json => "json",
+            StorageKind::Sqlite => "db",
+        }
+    }
+
+    pub fn open(self, path: PathBuf, backup_count: usize) -> Result<Box<dyn Storage>, Error> {
+        Ok(match self {
+            StorageKind::Json => Box::new(JsonStore::new(path, backup_count)),
+            StorageKind::Sqlite => Box::new(SqliteStore::open(&path)?),
+        })
+    }
+}
+
+impl FromStr for StorageKind {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<StorageKind, Error> {
+        match s {
+            "json" => Ok(StorageKind::Json),
+            "sqlite" => Ok(StorageKind::Sqlite),
+            _ => bail!("unknown storage {:?}, expected json or sqlite", s),
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use crate::app::AppState;
+use crate::storage::{migrations, Storage};
+use failure::{format_err, Error};
+use rusqlite::{params, Connection, NO_PARAMS};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const TODO_ITEMS: &str = "todo_items";
+const NOTES: &str = "notes";
+const PROJECTS: &str = "projects";
+const TABLES: [&str; 3] = [TODO_ITEMS, NOTES, PROJECTS];
+
+/// A single persisted todo, note or project. Everything but the keys used to
+/// rebuild the tree is kept as JSON so that the schema migrations apply to
+/// this store just like to the JSON one.
+#[derive(Debug, Clone, PartialEq)]
+struct Row {
+    parent_identifier: String,
+    position: i64,
+    data: String,
+}
+
+type Rows = HashMap<String, Row>;
+
+/// An embedded SQLite database with one row per todo, note and project. Saves
+/// only write the rows that changed since the last load or save.
+pub struct SqliteStore {
+    path: PathBuf,
+    connection: Connection,
+    saved: HashMap<&'static str, Rows>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<SqliteStore, Error> {
+        if let Some(dir) = path.parent().filter(|dir| *dir != Path::new("")) {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format_err!("couldn't create {}: {}", dir.display(), e))?;
+        }
+        let connection = Connection::open(path)
+            .map_err(|e| format_err!("couldn't open {}: {}", path.display(), e))?;
+        let mut schema = String::from(
+            "CREATE TABLE IF NOT EXISTS meta (key TEXT PRIMARY KEY, value TEXT NOT NULL);",
+        );
+        for table in TABLES.iter() {
+            schema.push_str(&format!(
+                "CREATE TABLE IF NOT EXISTS {} (
+                    identifier TEXT PRIMARY KEY,
+                    parent_identifier TEXT NOT NULL,
+                    position INTEGER NOT NULL,
+                    data TEXT NOT NULL
+                );",
+                table
+            ));
+        }
+        connection
+            .execute_batch(&schema)
+            .map_err(|e| format_err!("couldn't initialize {}: {}", path.display(), e))?;
+        Ok(SqliteStore {
+            path: path.to_path_buf(),
+            connection,
+            saved: HashMap::new(),
+        })
+    }
+
+    fn schema_version(&self) -> Result<Option<u64>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT value FROM meta WHERE key = 'schema_version'")?;
+        let mut versions = statement.query_map(NO_PARAMS, |row| row.get::<_, String>(0))?;
+        match versions.next() {
+            Some(version) => Ok(Some(version?.parse()?)),
+            None => Ok(None),
+        }
+    }
+
+    fn read_rows(&self, table: &str) -> Result<Rows, Error> {
+        let mut statement = self.connection.prepare(&format!(
+            "SELECT identifier, parent_identifier, position, data FROM {}",
+            table
+        ))?;
+        let rows = statement.query_map(NO_PARAMS, |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                Row {
+                    parent_identifier: row.get(1)?,
+                    position: row.get(2)?,
+                    data: row.get(3)?,
+                },
+            ))
+        })?;
+        let mut result = HashMap::new();
+        for row in rows {
+            let (identifier, row) = row?;
+            result.insert(identifier, row);
+        }
+        Ok(result)
+    }
+}
+
+impl Storage for SqliteStore {
+    fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn load(&mut self) -> Result<AppState, Error> {
+        let version = match self.schema_version()? {
+            Some(version) => version,
+            None => return Ok(AppState::empty()),
+        };
+        let mut saved = HashMap::new();
+        for table in TABLES.iter() {
+            saved.insert(*table, self.read_rows(table)?);
+        }
+        let state = assemble(version, &saved)?;
+        let state = migrations::migrate(state)?;
+        let app_state = serde_json::from_value(state)
+            .map_err(|e| format_err!("{} holds an invalid state: {}", self.path.display(), e))?;
+        self.saved = saved;
+        Ok(app_state)
+    }
+
+    fn save(&mut self, state: &AppState) -> Result<(), Error> {
+        let current = split(serde_json::to_value(state)?)?;
+        let changed = TABLES.iter().any(|table| {
+            self.saved.get(table).unwrap_or(&HashMap::new()) != current.get(table).unwrap()
+        });
+        if !changed && self.schema_version()? == Some(state.schema_version) {
+            return Ok(());
+        }
+
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES ('schema_version', ?1)",
+            params![state.schema_version.to_string()],
+        )?;
+        let empty = HashMap::new();
+        for table in TABLES.iter() {
+            let saved = self.saved.get(table).unwrap_or(&empty);
+            let rows = current.get(table).unwrap();
+            for (identifier, row) in rows {
+                if saved.get(identifier) != Some(row) {
+                    transaction.execute(
+                        &format!(
+                            "INSERT OR REPLACE INTO {} (identifier, parent_identifier, position, data)
+                            VALUES (?1, ?2, ?3, ?4)",
+                            table
+                        ),
+                        params![identifier, row.parent_identifier, row.position, row.data],
+                    )?;
+                }
+            }
+            for identifier in saved.keys().filter(|id| !rows.contains_key(*id)) {
+                transaction.execute(
+                    &format!("DELETE FROM {} WHERE identifier = ?1", table),
+                    params![identifier],
+                )?;
+            }
+        }
+        transaction.commit()?;
+        self.saved = current;
+        Ok(())
+    }
+}
+
+fn take_identifier(object: &mut Map<String, Value>) -> Result<String, Error> {
+    match object.remove("identifier") {
+        Some(Value::String(identifier)) => Ok(identifier),
+        _ => Err(format_err!("found an item without an identifier")),
+    }
+}
+
+fn objects(state: &mut Map<String, Value>, key: &str) -> Vec<Map<String, Value>> {
+    match state.remove(key) {
+        Some(Value::Array(items)) => items
+            .into_iter()
+            .filter_map(|item| match item {
+                Value::Object(object) => Some(object),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Breaks a serialized `AppState` down into the rows of each table.
+fn split(state: Value) -> Result<HashMap<&'static str, Rows>, Error> {
+    let mut state = match state {
+        Value::Object(state) => state,
+        _ => return Err(format_err!("the state must serialize to an object")),
+    };
+    let mut tables: HashMap<&'static str, Rows> = TABLES
+        .iter()
+        .map(|table| (*table, HashMap::new()))
+        .collect();
+
+    for (position, mut todo) in objects(&mut state, TODO_ITEMS).into_iter().enumerate() {
+        let identifier = take_identifier(&mut todo)?;
+        for (note_position, mut note) in objects(&mut todo, NOTES).into_iter().enumerate() {
+            let note_identifier = take_identifier(&mut note)?;
+            tables.get_mut(NOTES).unwrap().insert(
+                note_identifier,
+                Row {
+                    parent_identifier: identifier.clone(),
+                    position: note_position as i64,
+                    data: Value::Object(note).to_string(),
+                },
+            );
+        }
+        tables.get_mut(TODO_ITEMS).unwrap().insert(
+            identifier,
+            Row {
+                parent_identifier: String::new(),
+                position: position as i64,
+                data: Value::Object(todo).to_string(),
+            },
+        );
+    }
+    for (position, mut project) in objects(&mut state, PROJECTS).into_iter().enumerate() {
+        let identifier = take_identifier(&mut project)?;
+        tables.get_mut(PROJECTS).unwrap().insert(
+            identifier,
+            Row {
+                parent_identifier: String::new(),
+                position: position as i64,
+                data: Value::Object(project).to_string(),
+            },
+        );
+    }
+    Ok(tables)
+}
+
+/// The inverse of `split`: rebuilds the serialized `AppState` from the rows.
+fn assemble(version: u64, tables: &HashMap<&'static str, Rows>) -> Result<Value, Error> {
+    let ordered = |table: &str, parent: &str| -> Result<Vec<Map<String, Value>>, Error> {
+        let mut rows: Vec<_> = tables[table]
+            .iter()
+            .filter(|(_, row)| row.parent_identifier == parent)
+            .collect();
+        rows.sort_by_key(|(_, row)| row.position);
+        rows.into_iter()
+            .map(|(identifier, row)| {
+                let mut object = match serde_json::from_str(&row.data)? {
+                    Value::Object(object) => object,
+                    _ => return Err(format_err!("{} {} is not an object", table, identifier)),
+                };
+                object.insert(String::from("identifier"), json!(identifier));
+                Ok(object)
+            })
+            .collect()
+    };
+
+    let mut todo_items = vec![];
+    for mut todo in ordered(TODO_ITEMS, "")? {
+        let identifier = todo["identifier"].as_str().unwrap_or_default().to_string();
+        todo.insert(String::from(NOTES), json!(ordered(NOTES, &identifier)?));
+        todo_items.push(Value::Object(todo));
+    }
+    Ok(json!({
+        "schema_version": version,
+        "todo_items": todo_items,
+        "projects": ordered(PROJECTS, "")?,
+    }))
+}
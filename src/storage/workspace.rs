@@ -0,0 +1,121 @@
+use crate::storage::{Storage, StorageKind};
+use crate::todo::todo::SelectableItem;
+use failure::{bail, format_err, Error};
+use std::fs;
+use std::path::PathBuf;
+
+pub const DEFAULT_WORKSPACE: &str = "default";
+const STORE_FILE_STEM: &str = "code_pal";
+const LEGACY_STORE_FILE_NAME: &str = "code_pal.json";
+const WORKSPACES_DIR_NAME: &str = "workspaces";
+
+/// The directory holding every workspace, `$XDG_DATA_HOME/code_pal` (or the
+/// platform equivalent) unless overridden, and how their stores are opened.
+#[derive(Debug, Clone)]
+pub struct Workspaces {
+    pub root: PathBuf,
+    pub kind: StorageKind,
+    pub backup_count: usize,
+}
+
+impl Workspaces {
+    pub fn new(root: PathBuf, kind: StorageKind, backup_count: usize) -> Workspaces {
+        Workspaces {
+            root,
+            kind,
+            backup_count,
+        }
+    }
+
+    pub fn default_root() -> Result<PathBuf, Error> {
+        dirs::data_dir()
+            .map(|dir| dir.join("code_pal"))
+            .ok_or_else(|| format_err!("couldn't determine the data directory"))
+    }
+
+    /// The store backing the workspace `name`. The default workspace lives
+    /// directly in the root; older installs kept it as `~/code_pal.json`,
+    /// which is still used as long as nothing has been written to the new
+    /// location.
+    pub fn path(&self, name: &str) -> Result<PathBuf, Error> {
+        validate_name(name)?;
+        let extension = self.kind.extension();
+        if name == DEFAULT_WORKSPACE {
+            let path = self.root.join(format!("{}.{}", STORE_FILE_STEM, extension));
+            if !path.exists() && self.kind == StorageKind::Json {
+                if let Some(legacy) = dirs::home_dir().map(|home| home.join(LEGACY_STORE_FILE_NAME))
+                {
+                    if legacy.exists() {
+                        return Ok(legacy);
+                    }
+                }
+            }
+            return Ok(path);
+        }
+        Ok(self
+            .root
+            .join(WORKSPACES_DIR_NAME)
+            .join(format!("{}.{}", name, extension)))
+    }
+
+    pub fn open(&self, name: &str) -> Result<Box<dyn Storage>, Error> {
+        self.kind.open(self.path(name)?, self.backup_count)
+    }
+
+    /// The names of all workspaces that have a store on disk, always starting
+    /// with the default one.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(self.root.join(WORKSPACES_DIR_NAME))
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| {
+                        path.extension().and_then(|e| e.to_str()) == Some(self.kind.extension())
+                    })
+                    .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(String::from))
+                    .filter(|name| validate_name(name).is_ok() && name != DEFAULT_WORKSPACE)
+                    .collect()
+            })
+            .unwrap_or_default();
+        names.sort();
+        names.insert(0, String::from(DEFAULT_WORKSPACE));
+        names
+    }
+}
+
+pub fn validate_name(name: &str) -> Result<(), Error> {
+    if name.is_empty() {
+        bail!("workspace name can't be empty");
+    }
+    if !name
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    {
+        bail!(
+            "invalid workspace name {:?}: use letters, digits, '-' and '_' only",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// A workspace as offered by the workspace switcher.
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub name: String,
+    pub exists: bool,
+}
+
+impl SelectableItem for Workspace {
+    fn get_identifier(&mut self) -> String {
+        self.name.clone()
+    }
+    fn get_name(&mut self) -> String {
+        if self.exists {
+            self.name.clone()
+        } else {
+            format!("{} (new)", self.name)
+        }
+    }
+}
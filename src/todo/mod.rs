@@ -1,4 +1,6 @@
 pub mod todo;
 pub use todo::Note;
+pub use todo::Priority;
 pub use todo::Project;
+pub use todo::Status;
 pub use todo::TodoItem;
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -7,16 +8,132 @@ pub trait EditableStateItem {
 	fn get_content_mut(&mut self) -> &mut String;
 	fn get_identifier_mut(&mut self) -> &mut String;
 	fn new(s: String) -> Self;
+	/// What is shown before and after the content of the item in a list.
+	fn get_decorations(&self) -> (String, String) {
+		(String::new(), String::new())
+	}
+	/// The text shown for the item in a list, on one line.
+	fn get_label(&mut self) -> String {
+		let (prefix, suffix) = self.get_decorations();
+		let mut lines = self.get_content_mut().lines();
+		let first = lines.next().unwrap_or("").to_string();
+		match lines.count() {
+			0 => format!("{}{}{}", prefix, first, suffix),
+			more => format!("{}{} (+{} lines){}", prefix, first, more, suffix),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+	Todo,
+	InProgress,
+	Blocked,
+	Done,
+}
+
+impl Status {
+	pub fn next(self) -> Status {
+		match self {
+			Status::Todo => Status::InProgress,
+			Status::InProgress => Status::Blocked,
+			Status::Blocked => Status::Done,
+			Status::Done => Status::Todo,
+		}
+	}
+	pub fn marker(self) -> &'static str {
+		match self {
+			Status::Todo => "[ ]",
+			Status::InProgress => "[~]",
+			Status::Blocked => "[!]",
+			Status::Done => "[x]",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+	Low,
+	Normal,
+	High,
+}
+
+impl Priority {
+	pub fn next(self) -> Priority {
+		match self {
+			Priority::Low => Priority::Normal,
+			Priority::Normal => Priority::High,
+			Priority::High => Priority::Low,
+		}
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TodoItem {
 	pub identifier: String,
 	pub title: String,
-	#[serde(default)]
 	pub project_identifier: String,
 	pub description: Vec<String>,
 	pub notes: Rc<RefCell<Vec<Note>>>,
+	pub status: Status,
+	pub priority: Priority,
+	pub due: Option<NaiveDate>,
+	/// The git branch the todo is worked on, in its project's directory.
+	pub branch: Option<String>,
+	pub created_at: DateTime<Utc>,
+	pub updated_at: DateTime<Utc>,
+}
+
+impl TodoItem {
+	/// The `#tags` in the title, without the leading `#`.
+	pub fn tags(&self) -> Vec<&str> {
+		self.title
+			.split_whitespace()
+			.filter_map(|word| word.strip_prefix('#'))
+			.map(|tag| tag.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '-' && c != '_'))
+			.filter(|tag| !tag.is_empty())
+			.collect()
+	}
+
+	pub fn is_overdue(&self, today: NaiveDate) -> bool {
+		self.status != Status::Done && self.due.is_some_and(|due| due < today)
+	}
+
+	/// The next due date offered by the due date shortcut: none, today,
+	/// tomorrow, in a week and back to none.
+	pub fn next_due(&self, today: NaiveDate) -> Option<NaiveDate> {
+		let tomorrow = today.succ();
+		let next_week = today + chrono::Duration::days(7);
+		match self.due {
+			None => Some(today),
+			Some(due) if due < tomorrow => Some(tomorrow),
+			Some(due) if due < next_week => Some(next_week),
+			Some(_) => None,
+		}
+	}
+
+	/// A due date as typed, `YYYY-MM-DD`, or none when nothing is.
+	pub fn parse_due(text: &str) -> Result<Option<NaiveDate>, chrono::ParseError> {
+		match text.trim() {
+			"" => Ok(None),
+			text => NaiveDate::parse_from_str(text, "%Y-%m-%d").map(Some),
+		}
+	}
+
+	/// A branch name made of the words of the title, without its tags,
+	/// e.g. `fix-the-login-form`.
+	pub fn suggested_branch(&self) -> String {
+		self.title
+			.split_whitespace()
+			.filter(|word| !word.starts_with('#'))
+			.flat_map(|word| word.split(|c: char| !c.is_alphanumeric()))
+			.filter(|word| !word.is_empty())
+			.map(|word| word.to_lowercase())
+			.collect::<Vec<_>>()
+			.join("-")
+	}
 }
 
 impl EditableStateItem for TodoItem {
@@ -33,8 +150,28 @@ impl EditableStateItem for TodoItem {
 			description: vec![],
 			notes: Rc::new(RefCell::new(vec![Note::new(String::from(""))])),
 			project_identifier: String::from(""),
+			status: Status::Todo,
+			priority: Priority::Normal,
+			due: None,
+			branch: None,
+			created_at: Utc::now(),
+			updated_at: Utc::now(),
 		}
 	}
+	fn get_decorations(&self) -> (String, String) {
+		let priority = match self.priority {
+			Priority::High => "! ",
+			_ => "",
+		};
+		let due = match self.due {
+			Some(due) if self.is_overdue(Local::today().naive_local()) => {
+				format!(" (overdue {})", due)
+			}
+			Some(due) => format!(" (due {})", due),
+			None => String::new(),
+		};
+		(format!("{} {}", self.status.marker(), priority), due)
+	}
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -1,14 +1,23 @@
 #[cfg(feature = "termion")]
 pub mod event;
 
+use tui::layout::Rect;
+use unicode_width::UnicodeWidthStr;
+
 pub struct TabsState<'a> {
     pub titles: Vec<&'a str>,
     pub index: usize,
+    /// Where the tabs were last drawn, for the mouse.
+    pub area: Rect,
 }
 
 impl<'a> TabsState<'a> {
     pub fn new(titles: Vec<&'a str>) -> TabsState {
-        TabsState { titles, index: 0 }
+        TabsState {
+            titles,
+            index: 0,
+            area: Rect::default(),
+        }
     }
     pub fn next(&mut self) {
         self.index = (self.index + 1) % self.titles.len();
@@ -21,4 +30,18 @@ impl<'a> TabsState<'a> {
             self.index = self.titles.len() - 1;
         }
     }
+
+    /// The tab whose title is at `column`, laid out as `Tabs` does with a
+    /// space either side of each title and a divider between them.
+    pub fn title_at(&self, column: u16) -> Option<usize> {
+        let mut x = self.area.left() + 1;
+        for (i, title) in self.titles.iter().enumerate() {
+            let end = x + title.width() as u16 + 2;
+            if column >= x && column < end {
+                return Some(i);
+            }
+            x = end + 1;
+        }
+        None
+    }
 }
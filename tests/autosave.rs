@@ -0,0 +1,81 @@
+mod common;
+
+use code_pal::app::{ui, App, CodePalAction, Event};
+use common::{ctrl, key, press};
+use crossterm::event::KeyCode;
+use std::fs;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
+use tui::backend::TestBackend;
+use tui::Terminal;
+
+/// Types `text` into a new todo.
+fn type_todo(app: &mut App, receiver: &Receiver<Event>, text: &str) {
+    press(app, receiver, ctrl('a'));
+    common::type_text(app, receiver, text);
+}
+
+/// The second line of the screen, inside the tab bar.
+fn tab_bar(app: &mut App) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    ui::draw(&mut terminal, app).unwrap();
+    let buffer = terminal.backend().buffer();
+    (0..80).map(|x| buffer.get(x, 1).symbol.clone()).collect()
+}
+
+#[test]
+fn edits_are_saved_after_a_pause() {
+    let root = common::temp_dir();
+    let (mut app, receiver) = common::app(&root);
+    assert!(!tab_bar(&mut app).contains("unsaved"));
+
+    app.autosave_delay = Some(Duration::from_secs(3600));
+    type_todo(&mut app, &receiver, "ship it");
+    assert!(app.dirty);
+    assert!(tab_bar(&mut app).contains("● unsaved"));
+    app.on_tick();
+    assert!(app.dirty);
+    assert!(!root.join("todos.json").exists());
+
+    app.autosave_delay = Some(Duration::from_secs(0));
+    app.on_tick();
+    assert!(!app.dirty);
+    let saved = fs::read_to_string(root.join("todos.json")).unwrap();
+    assert!(saved.contains("ship it"));
+    assert!(!tab_bar(&mut app).contains("unsaved"));
+    fs::remove_dir_all(root).unwrap_or_default();
+}
+
+#[test]
+fn quitting_asks_about_unsaved_changes() {
+    let root = common::temp_dir();
+    let (mut app, receiver) = common::app(&root);
+    press(&mut app, &receiver, ctrl('q'));
+    assert!(app.should_quit);
+
+    let (mut app, receiver) = common::app(&root);
+    type_todo(&mut app, &receiver, "draft");
+    press(&mut app, &receiver, ctrl('q'));
+    assert!(!app.should_quit);
+    assert_eq!(app.current_action, CodePalAction::ConfirmQuit);
+    // Other keys keep asking, esc goes back to the todos.
+    press(&mut app, &receiver, key(KeyCode::Char('x')));
+    assert_eq!(app.current_action, CodePalAction::ConfirmQuit);
+    press(&mut app, &receiver, key(KeyCode::Esc));
+    assert_eq!(app.current_action, CodePalAction::None);
+    assert!(!app.should_quit && app.dirty);
+
+    press(&mut app, &receiver, ctrl('q'));
+    press(&mut app, &receiver, key(KeyCode::Char('d')));
+    assert!(app.should_quit);
+    assert!(!root.join("todos.json").exists());
+
+    let (mut app, receiver) = common::app(&root);
+    type_todo(&mut app, &receiver, "final");
+    press(&mut app, &receiver, ctrl('q'));
+    press(&mut app, &receiver, key(KeyCode::Char('s')));
+    assert!(app.should_quit);
+    let saved = fs::read_to_string(root.join("todos.json")).unwrap();
+    assert!(saved.contains("final"));
+    fs::remove_dir_all(root).unwrap_or_default();
+}
@@ -0,0 +1,132 @@
+//! Helpers shared by the integration tests, each test crate uses some.
+#![allow(dead_code)]
+
+use code_pal::app::theme::Theme;
+use code_pal::app::ui_component::{TableEditor, UIComponent};
+use code_pal::app::{App, AppState, Event};
+use code_pal::config::LayoutConfig;
+use code_pal::storage::{JsonStore, StorageKind, Workspaces};
+use code_pal::todo::todo::{EditableRowItem, EditableStateItem};
+use code_pal::todo::{Priority, Project, Status, TodoItem};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver};
+use tui::backend::TestBackend;
+use tui::layout::Rect;
+use tui::Terminal;
+
+/// A new, empty directory under the system's temporary directory.
+pub fn temp_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("code_pal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// An app with the empty state, saving to a JSON store in `root`, and
+/// the events its components send.
+pub fn app(root: &Path) -> (App<'static>, Receiver<Event>) {
+    let store = JsonStore::new(root.join("todos.json"), 0);
+    let workspaces = Workspaces::new(root.to_path_buf(), StorageKind::Json, 0);
+    let (sender, receiver) = channel();
+    let app = App::new(
+        "Code Pal",
+        AppState::empty(),
+        Box::new(store),
+        workspaces,
+        String::from("default"),
+        sender,
+    );
+    (app, receiver)
+}
+
+/// Hands the app what its components sent, as the main loop does.
+pub fn pump(app: &mut App, receiver: &Receiver<Event>) {
+    while let Ok(event) = receiver.try_recv() {
+        if let Event::Action(action) = event {
+            app.on_action(action);
+        }
+    }
+}
+
+/// Presses `event` and hands the app what came of it.
+pub fn press(app: &mut App, receiver: &Receiver<Event>, event: KeyEvent) {
+    app.on_key(event);
+    pump(app, receiver);
+}
+
+/// Presses the keys of `text`, one character at a time.
+pub fn type_text(app: &mut App, receiver: &Receiver<Event>, text: &str) {
+    for c in text.chars() {
+        press(app, receiver, key(KeyCode::Char(c)));
+    }
+}
+
+pub fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::empty())
+}
+
+pub fn ctrl(c: char) -> KeyEvent {
+    KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+}
+
+/// A todo of `project`, by identifier, with no notes.
+pub fn todo(title: &str, status: Status, priority: Priority, project: &str) -> TodoItem {
+    let mut todo = TodoItem::new(title.to_string());
+    todo.status = status;
+    todo.priority = priority;
+    todo.project_identifier = project.to_string();
+    todo
+}
+
+pub fn project(identifier: &str, name: &str, url: &str, dir_location: &str) -> Project {
+    Project {
+        identifier: identifier.to_string(),
+        name: name.to_string(),
+        url: url.to_string(),
+        dir_location: dir_location.to_string(),
+    }
+}
+
+/// The projects table as the app sets it up, over `count` projects named
+/// `project 0` and on.
+pub fn project_table(count: usize) -> TableEditor<Project> {
+    let projects: Vec<_> = (0..count)
+        .map(|i| Project::new(vec![format!("project {}", i), String::new(), String::new()]))
+        .collect();
+    let (sender, _receiver) = channel();
+    TableEditor::new(
+        String::from("Projects"),
+        Some(Rc::new(RefCell::new(projects))),
+        vec![
+            String::from("Name"),
+            String::from("url"),
+            String::from("Directory"),
+        ],
+        LayoutConfig::default().project_columns,
+        sender,
+    )
+}
+
+/// Draws `component` in `area` of `terminal`, returns the lines of the
+/// screen.
+pub fn draw(
+    terminal: &mut Terminal<TestBackend>,
+    component: &mut impl UIComponent,
+    area: Rect,
+) -> Vec<String> {
+    terminal
+        .draw(|mut f| component.draw(&mut f, area, &Theme::default()))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let size = buffer.area;
+    (size.top()..size.bottom())
+        .map(|y| {
+            (size.left()..size.right())
+                .map(|x| buffer.get(x, y).symbol.clone())
+                .collect()
+        })
+        .collect()
+}
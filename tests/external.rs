@@ -0,0 +1,74 @@
+mod common;
+
+use code_pal::app::external::{self, ExternalTarget};
+use code_pal::app::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+/// Makes `script` the editor, run with the file to edit.
+fn use_editor(dir: &Path, script: &str) {
+    let path = dir.join(format!("editor-{}.sh", uuid::Uuid::new_v4()));
+    fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    std::env::set_var("VISUAL", &path);
+}
+
+fn key(app: &mut App, code: KeyCode, modifiers: KeyModifiers) {
+    app.on_key(KeyEvent::new(code, modifiers));
+}
+
+/// Opens what alt+e opens and runs the editor on it, as the main loop does.
+fn open_in_editor(app: &mut App) -> ExternalTarget {
+    key(app, KeyCode::Char('e'), KeyModifiers::ALT);
+    let edit = app.external_edit.take().unwrap();
+    let target = edit.target.clone();
+    let result = external::edit(&edit.content);
+    app.on_external_edit(edit, result);
+    target
+}
+
+// One test, since the editor is chosen through the environment.
+#[test]
+fn editors_replace_the_text_in_one_undoable_step() {
+    let root = common::temp_dir();
+    use_editor(&root, "printf 'rewritten\\nline two\\n' > \"$1\"");
+    assert_eq!(external::edit("old").unwrap(), "rewritten\nline two");
+    // The final line break stays when the text had one.
+    assert_eq!(external::edit("old\n").unwrap(), "rewritten\nline two\n");
+
+    let (mut app, _receiver) = common::app(&root);
+    let description = |app: &App| app.app_state.todo_items.borrow()[0].description.clone();
+    let note = |app: &App| {
+        app.app_state.todo_items.borrow()[0].notes.borrow()[0]
+            .content
+            .clone()
+    };
+    key(&mut app, KeyCode::Char('a'), KeyModifiers::CONTROL);
+    let target = open_in_editor(&mut app);
+    assert!(matches!(target, ExternalTarget::Description(_)));
+    assert_eq!(description(&app), vec!["rewritten", "line two"]);
+    assert!(app.dirty);
+
+    key(&mut app, KeyCode::Char('n'), KeyModifiers::CONTROL);
+    let target = open_in_editor(&mut app);
+    assert!(matches!(target, ExternalTarget::Note(_, 0)));
+    assert_eq!(note(&app), "rewritten\nline two");
+
+    app.on_undo();
+    assert_eq!(note(&app), "");
+    assert_eq!(description(&app), vec!["rewritten", "line two"]);
+    app.on_undo();
+    assert!(description(&app).is_empty());
+
+    // A failing editor changes nothing and says so.
+    use_editor(&root, "echo changed > \"$1\"; exit 3");
+    assert!(external::edit("old").is_err());
+    key(&mut app, KeyCode::Char('a'), KeyModifiers::CONTROL);
+    open_in_editor(&mut app);
+    assert!(description(&app).is_empty());
+    assert!(app.message.as_deref().unwrap().starts_with("Editor failed"));
+    std::env::remove_var("VISUAL");
+    fs::remove_dir_all(root).unwrap_or_default();
+}
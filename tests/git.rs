@@ -0,0 +1,101 @@
+use chrono::{TimeZone, Utc};
+use code_pal::git::{self, git, parse_log, parse_status};
+use std::fs;
+use std::path::Path;
+
+/// Commits everything in `dir` as a test author, whatever the user's config.
+fn commit(dir: &Path, message: &str) {
+    let author = ["-c", "user.name=Test", "-c", "user.email=test@example.com"];
+    git(dir, &["add", "-A"]).unwrap();
+    let mut args = author.to_vec();
+    args.extend(&["-c", "commit.gpgsign=false", "commit", "-q", "-m", message]);
+    git(dir, &args).unwrap();
+}
+
+#[test]
+fn reads_porcelain_status_and_log_lines() {
+    let status = "# branch.oid 1a2b3c\n# branch.head feature/login\n# branch.upstream origin/feature/login\n# branch.ab +2 -1\n1 .M N... 100644 100644 100644 1a 1a src/main.rs\n";
+    assert_eq!(
+        parse_status(status),
+        (Some(String::from("feature/login")), true, Some((2, 1)))
+    );
+    let detached = "# branch.oid 1a2b3c\n# branch.head (detached)\n";
+    assert_eq!(parse_status(detached), (None, false, None));
+
+    let log = parse_log("1a2b3c4\u{0}1600000000\u{0}Fix the login form\nbroken line\n");
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].hash, "1a2b3c4");
+    assert_eq!(log[0].summary, "Fix the login form");
+    let now = Utc.timestamp_opt(1600000000 + 3 * 3600 + 5, 0).unwrap();
+    assert_eq!(log[0].age(now), "3 hours ago");
+}
+
+#[test]
+fn inspects_a_repository_on_disk() {
+    let dir = std::env::temp_dir().join(format!("code_pal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    assert!(git::status(&dir).is_err());
+
+    git(&dir, &["init", "-q", "-b", "main"]).unwrap();
+    let empty = git::status(&dir).unwrap();
+    assert_eq!(empty.branch.as_deref(), Some("main"));
+    assert_eq!(empty.last_commit, None);
+
+    fs::write(dir.join("README.md"), "hello").unwrap();
+    assert!(git::status(&dir).unwrap().dirty);
+    commit(&dir, "Add a readme");
+    let status = git::status(&dir).unwrap();
+    assert!(!status.dirty);
+    assert_eq!(status.ahead_behind, None);
+    assert_eq!(status.last_commit.unwrap().summary, "Add a readme");
+
+    // Polling leaves the index to the user's own git commands, even when
+    // its record of a file is out of date.
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    fs::write(dir.join("README.md"), "hello").unwrap();
+    let index = fs::read(dir.join(".git").join("index")).unwrap();
+    assert!(!git::status(&dir).unwrap().dirty);
+    assert_eq!(fs::read(dir.join(".git").join("index")).unwrap(), index);
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn checks_out_branches_and_lists_their_own_commits() {
+    let dir = std::env::temp_dir().join(format!("code_pal_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&dir).unwrap();
+    assert!(git::branch_commits(&dir, "feature/login").is_err());
+    git(&dir, &["init", "-q", "-b", "main"]).unwrap();
+    fs::write(dir.join("README.md"), "hello").unwrap();
+    commit(&dir, "Add a readme");
+
+    assert_eq!(git::branch_commits(&dir, "feature/login").unwrap(), None);
+    assert!(!git::valid_branch_name("not a branch"));
+    assert!(!git::valid_branch_name("-login"));
+    for name in &[
+        "", "HEAD", "a..b", "a//b", "a/", "a/.b", "a.lock", "a.", "a@{b", "a~1",
+    ] {
+        assert!(!git::valid_branch_name(name), "{:?}", name);
+    }
+    assert!(git::valid_branch_name("feature/login"));
+    assert!(git::checkout(&dir, "not a branch").is_err());
+    assert!(git::checkout(&dir, "feature/login").unwrap());
+    assert_eq!(
+        git::status(&dir).unwrap().branch.as_deref(),
+        Some("feature/login")
+    );
+    assert_eq!(
+        git::branch_commits(&dir, "feature/login").unwrap(),
+        Some(vec![])
+    );
+
+    fs::write(dir.join("login.rs"), "fn login() {}").unwrap();
+    commit(&dir, "Add the login form");
+    let commits = git::branch_commits(&dir, "feature/login").unwrap().unwrap();
+    let summaries: Vec<_> = commits.iter().map(|c| c.summary.as_str()).collect();
+    assert_eq!(summaries, vec!["Add the login form"]);
+
+    // Checking out an existing branch doesn't create it again.
+    assert!(!git::checkout(&dir, "main").unwrap());
+    assert_eq!(git::status(&dir).unwrap().branch.as_deref(), Some("main"));
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
@@ -0,0 +1,62 @@
+use code_pal::app::help::sections;
+use code_pal::app::keymap::Keymap;
+use code_pal::config::Config;
+
+fn keys_of<'a>(sections: &'a [code_pal::app::help::HelpSection], title: &str) -> Vec<&'a str> {
+    sections
+        .iter()
+        .find(|section| section.title == title)
+        .unwrap()
+        .keys
+        .iter()
+        .map(|(keys, _)| keys.as_str())
+        .collect()
+}
+
+#[test]
+fn help_follows_the_keymap() {
+    let config = Config::parse("[keys.global]\nsave = [\"f2\", \"ctrl+s\"]\nquit = []").unwrap();
+    let keymap = Keymap::from_config(&config.keys).unwrap();
+    let help = sections(&keymap, false);
+    let global = keys_of(&help, "Everywhere");
+    assert!(global.contains(&"f2, ctrl+s"));
+    assert!(!global.contains(&"ctrl+q"));
+    assert_eq!(
+        keys_of(&help, "Project and workspace pickers"),
+        vec![
+            "up",
+            "down",
+            "pageup",
+            "pagedown",
+            "ctrl+home",
+            "ctrl+end",
+            "enter"
+        ]
+    );
+    assert!(help
+        .iter()
+        .all(|section| !section.title.starts_with("Normal mode")));
+}
+
+#[test]
+fn help_lists_normal_mode_keys_when_modal() {
+    let help = sections(&Keymap::default(), true);
+    let normal = keys_of(&help, "Normal mode of the todo list and notes");
+    assert!(normal.contains(&"gg"));
+    assert!(normal.contains(&"dd"));
+}
+
+#[test]
+fn help_lists_the_keys_handled_outside_the_keymap() {
+    let config = Config::parse("[keys.text]\npaste = \"alt+v\"").unwrap();
+    let help = sections(&Keymap::from_config(&config.keys).unwrap(), false);
+    let text = keys_of(&help, "While editing text");
+    assert!(text.contains(&"alt+v"));
+    assert!(text.contains(&"ctrl+c"));
+    assert!(text.contains(&"shift+any of the above"));
+    assert_eq!(
+        keys_of(&help, "Asked about unsaved changes"),
+        vec!["s, y", "d, n", "esc"]
+    );
+    assert!(keys_of(&help, "This help").contains(&"q, ?, f1, esc"));
+}
@@ -0,0 +1,159 @@
+use code_pal::app::history::{
+    Command, EditKind, Focus, History, InsertRow, LinesEdit, RemoveRow, SetField, TextEdit,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn lines(item: &mut Vec<String>) -> &mut Vec<String> {
+    item
+}
+
+fn text(item: &mut String, _: usize) -> &mut String {
+    item
+}
+
+fn whole(item: &mut String) -> &mut String {
+    item
+}
+
+fn list(items: &[&str]) -> Rc<RefCell<Vec<String>>> {
+    Rc::new(RefCell::new(items.iter().map(|s| s.to_string()).collect()))
+}
+
+/// Applies and records a text edit of the first row.
+fn edit(
+    history: &mut History,
+    list: &Rc<RefCell<Vec<String>>>,
+    kind: EditKind,
+    (position, removed, inserted): (usize, &str, &str),
+) {
+    let mut command = Box::new(TextEdit {
+        list: list.clone(),
+        index: 0,
+        field: 0,
+        accessor: text,
+        position,
+        removed: removed.to_string(),
+        inserted: inserted.to_string(),
+        kind,
+    });
+    command.apply();
+    history.record(command, Focus::TodoItems(0));
+}
+
+#[test]
+fn rows_are_inserted_and_removed_reversibly() {
+    let items = list(&["a", "c"]);
+    let mut insert = InsertRow {
+        list: items.clone(),
+        index: 1,
+        item: Some(String::from("b")),
+    };
+    insert.apply();
+    assert_eq!(*items.borrow(), vec!["a", "b", "c"]);
+    assert!(!insert.in_place());
+    insert.revert();
+    assert_eq!(*items.borrow(), vec!["a", "c"]);
+    insert.apply();
+    assert_eq!(*items.borrow(), vec!["a", "b", "c"]);
+
+    let mut remove = RemoveRow {
+        list: items.clone(),
+        index: 0,
+        item: None,
+    };
+    remove.apply();
+    assert_eq!(*items.borrow(), vec!["b", "c"]);
+    remove.revert();
+    assert_eq!(*items.borrow(), vec!["a", "b", "c"]);
+    assert_eq!(remove.index(), 0);
+}
+
+#[test]
+fn fields_are_swapped_back_and_forth() {
+    let items = list(&["todo"]);
+    let mut set = SetField {
+        list: items.clone(),
+        index: 0,
+        accessor: whole,
+        value: String::from("done"),
+    };
+    set.apply();
+    assert_eq!(items.borrow()[0], "done");
+    set.revert();
+    assert_eq!(items.borrow()[0], "todo");
+    assert!(set.in_place());
+}
+
+#[test]
+fn typing_is_undone_a_word_at_a_time() {
+    let items = list(&[""]);
+    let mut history = History::new();
+    for (position, c) in "ship it".char_indices() {
+        edit(
+            &mut history,
+            &items,
+            EditKind::Typing,
+            (position, "", &c.to_string()),
+        );
+    }
+    assert_eq!(items.borrow()[0], "ship it");
+    assert_eq!(history.undo(), Some(Focus::TodoItems(0)));
+    assert_eq!(items.borrow()[0], "ship ");
+    history.undo();
+    assert_eq!(items.borrow()[0], "");
+    assert_eq!(history.undo(), None);
+    history.redo();
+    assert_eq!(items.borrow()[0], "ship ");
+    // A new edit drops what could have been redone.
+    edit(&mut history, &items, EditKind::Typing, (5, "", "x"));
+    assert_eq!(history.redo(), None);
+    assert_eq!(items.borrow()[0], "ship x");
+}
+
+#[test]
+fn deleting_merges_backwards_and_forwards_but_not_with_typing() {
+    let items = list(&["one two"]);
+    let mut history = History::new();
+    // Backspace from the end, then delete at the start.
+    edit(&mut history, &items, EditKind::Deleting, (6, "o", ""));
+    edit(&mut history, &items, EditKind::Deleting, (5, "w", ""));
+    edit(&mut history, &items, EditKind::Typing, (5, "", "en"));
+    edit(&mut history, &items, EditKind::Deleting, (0, "o", ""));
+    edit(&mut history, &items, EditKind::Deleting, (0, "n", ""));
+    assert_eq!(items.borrow()[0], "e ten");
+    history.undo();
+    assert_eq!(items.borrow()[0], "one ten");
+    history.undo();
+    assert_eq!(items.borrow()[0], "one t");
+    history.undo();
+    assert_eq!(items.borrow()[0], "one two");
+    assert_eq!(history.undo(), None);
+}
+
+#[test]
+fn lines_edits_split_lines_and_undo_by_word() {
+    let list = Rc::new(RefCell::new(vec![vec![String::from("first")]]));
+    let mut history = History::new();
+    for (position, inserted) in [(5, "\n"), (6, "s"), (7, "e"), (8, "c")].iter() {
+        let mut command = Box::new(LinesEdit {
+            list: list.clone(),
+            index: 0,
+            accessor: lines,
+            position: *position,
+            removed: String::new(),
+            inserted: inserted.to_string(),
+            kind: EditKind::Typing,
+        });
+        command.apply();
+        history.record(command, Focus::TodoItems(0));
+    }
+    assert_eq!(list.borrow()[0], vec!["first", "sec"]);
+    history.undo();
+    assert_eq!(list.borrow()[0], vec!["first", ""]);
+    history.undo();
+    assert_eq!(list.borrow()[0], vec!["first"]);
+    history.redo();
+    history.redo();
+    assert_eq!(list.borrow()[0], vec!["first", "sec"]);
+}
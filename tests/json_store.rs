@@ -0,0 +1,119 @@
+mod common;
+
+use code_pal::app::AppState;
+use code_pal::storage::json::write_atomic;
+use code_pal::storage::{CorruptStore, JsonStore, Recovery, Storage};
+use std::fs;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn atomic_writes_replace_the_file_and_leave_nothing_behind() {
+    let dir = common::temp_dir();
+    let path = dir.join("todos.json");
+    fs::write(&path, "old").unwrap();
+    write_atomic(&path, b"new").unwrap();
+    assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+    let names: Vec<_> = fs::read_dir(&dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(names, vec!["todos.json"]);
+
+    // A failed write leaves neither the file nor its temporary sibling.
+    let missing = dir.join("missing").join("todos.json");
+    assert!(write_atomic(&missing, b"new").is_err());
+    assert!(!dir.join("missing").exists());
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn saving_keeps_only_the_newest_backups() {
+    let dir = common::temp_dir();
+    let store = JsonStore::new(dir.join("todos.json"), 2);
+    store.write("first").unwrap();
+    assert!(store.backups().unwrap().is_empty());
+    for contents in &["second", "third", "fourth"] {
+        // Backups are named after the time to the millisecond.
+        thread::sleep(Duration::from_millis(5));
+        store.write(contents).unwrap();
+    }
+    // Writing the same contents again doesn't make a backup.
+    store.write("fourth").unwrap();
+
+    let backups = store.backups().unwrap();
+    assert_eq!(backups.len(), 2);
+    assert_eq!(fs::read_to_string(&backups[0]).unwrap(), "third");
+    assert_eq!(fs::read_to_string(&backups[1]).unwrap(), "second");
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn restoring_gives_back_the_backed_up_bytes() {
+    let dir = common::temp_dir();
+    let store = JsonStore::new(dir.join("todos.json"), 5);
+    let original = "{\"todo_items\": [\"caf\u{e9}\"]}\r\n";
+    store.write(original).unwrap();
+    thread::sleep(Duration::from_millis(5));
+    store.write("{}").unwrap();
+
+    let backup = store.backups().unwrap()[0].clone();
+    thread::sleep(Duration::from_millis(5));
+    store.restore(&backup).unwrap();
+    assert_eq!(fs::read(&store.path).unwrap(), original.as_bytes());
+    // The store that was replaced can be restored in turn.
+    assert_eq!(
+        fs::read_to_string(&store.backups().unwrap()[0]).unwrap(),
+        "{}"
+    );
+    assert!(store.restore(&dir.join("nothing.bak")).is_err());
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+/// A store holding one todo titled `title`.
+fn state_json(title: &str) -> String {
+    let state = AppState::empty();
+    state.todo_items.borrow_mut()[0].title = title.to_string();
+    serde_json::to_string(&state).unwrap()
+}
+
+#[test]
+fn corrupt_stores_are_quarantined_for_the_last_good_backup() {
+    let dir = common::temp_dir();
+    let mut store = JsonStore::new(dir.join("todos.json"), 5);
+    let good = state_json("good");
+    store.write(&good).unwrap();
+    thread::sleep(Duration::from_millis(5));
+    store.write("{\"todo_items\": [").unwrap();
+    thread::sleep(Duration::from_millis(5));
+    store.write("{\"todo_items\": [}").unwrap();
+
+    let error = store.load().err().unwrap();
+    assert!(error.downcast_ref::<CorruptStore>().is_some());
+    // The newest backup is corrupt too, so the one before it is used.
+    let backup = store.last_good_backup().unwrap();
+    assert_eq!(fs::read_to_string(&backup).unwrap(), good);
+
+    let (state, quarantined) = store.recover(&Recovery::Backup(backup)).unwrap();
+    assert_eq!(state.todo_items.borrow()[0].title, "good");
+    assert_eq!(
+        fs::read_to_string(&quarantined).unwrap(),
+        "{\"todo_items\": [}"
+    );
+    assert_eq!(store.load().unwrap().todo_items.borrow()[0].title, "good");
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn corrupt_stores_can_be_started_over() {
+    let dir = common::temp_dir();
+    let mut store = JsonStore::new(dir.join("todos.json"), 5);
+    store.write("not json").unwrap();
+    assert_eq!(store.last_good_backup(), None);
+
+    let (state, quarantined) = store.recover(&Recovery::Fresh).unwrap();
+    assert_eq!(state.todo_items.borrow().len(), 1);
+    assert_eq!(fs::read_to_string(quarantined).unwrap(), "not json");
+    assert!(!store.exists());
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
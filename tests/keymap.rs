@@ -0,0 +1,85 @@
+use code_pal::app::keymap::{Action, Context, KeyChord, Keymap};
+use code_pal::config::Config;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+    KeyEvent::new(code, modifiers)
+}
+
+fn keymap(toml: &str) -> Result<Keymap, failure::Error> {
+    Keymap::from_config(&Config::parse(toml)?.keys)
+}
+
+#[test]
+fn chords_parse_the_way_terminals_send_them() {
+    let chord = |s: &str| s.parse::<KeyChord>().unwrap();
+    assert_eq!(
+        chord("ctrl+s"),
+        KeyChord::new(KeyCode::Char('s'), KeyModifiers::CONTROL)
+    );
+    assert_eq!(chord("shift+a"), chord("A"));
+    assert_eq!(chord("ctrl+j"), chord("enter"));
+    assert_eq!(chord("shift+tab"), chord("backtab"));
+    assert_eq!(chord("alt++").to_string(), "alt++");
+    assert_eq!(chord("Ctrl+Alt+PageDown").to_string(), "ctrl+alt+pagedown");
+    assert!("hyper+x".parse::<KeyChord>().is_err());
+    assert!("ctrl+nope".parse::<KeyChord>().is_err());
+}
+
+#[test]
+fn presets_are_free_of_conflicts_and_apply_per_context() {
+    for preset in &["default", "emacs", "vim"] {
+        keymap(&format!("[keys]\npreset = {:?}", preset)).unwrap();
+    }
+    let emacs = keymap("[keys]\npreset = \"emacs\"").unwrap();
+    let ctrl_n = key(KeyCode::Char('n'), KeyModifiers::CONTROL);
+    assert_eq!(
+        emacs.action(Some(Context::Notes), ctrl_n),
+        Some(Action::Down)
+    );
+    assert_eq!(emacs.action(None, ctrl_n), None);
+    let default = Keymap::default();
+    assert_eq!(default.action(None, ctrl_n), Some(Action::AddNote));
+    let alt_s = key(KeyCode::Char('s'), KeyModifiers::ALT);
+    assert_eq!(
+        default.action(Some(Context::TodoList), alt_s),
+        Some(Action::CycleStatus)
+    );
+    assert_eq!(default.action(Some(Context::Notes), alt_s), None);
+}
+
+#[test]
+fn config_overrides_bindings_and_reports_conflicts() {
+    let keys = keymap("[keys.global]\nsave = [\"ctrl+b\", \"f2\"]\nquit = []").unwrap();
+    assert_eq!(
+        keys.action(None, key(KeyCode::F(2), KeyModifiers::empty())),
+        Some(Action::Save)
+    );
+    assert_eq!(
+        keys.action(None, key(KeyCode::Char('q'), KeyModifiers::CONTROL)),
+        None
+    );
+
+    let error = keymap("[keys.global]\nsave = \"ctrl+f\"\n\n[keys.notes]\ndown = \"ctrl+a\"")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("ctrl+f is bound to global.save and global.search"));
+    assert!(error.contains("ctrl+a is bound to global.add_todo and notes.down"));
+
+    // The editing chords are bindings too, and are in the way of others.
+    let error = keymap("[keys.global]\nsave = \"ctrl+v\"")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("ctrl+v is bound to global.save and text.paste"));
+    let keys = keymap("[keys.text]\npaste = \"alt+v\"").unwrap();
+    assert_eq!(
+        keys.action(
+            Some(Context::Notes),
+            key(KeyCode::Char('v'), KeyModifiers::ALT)
+        ),
+        Some(Action::Paste)
+    );
+
+    assert!(keymap("[keys.notes]\nchoose = \"enter\"").is_err());
+    assert!(keymap("[keys.sidebar]\nup = \"k\"").is_err());
+}
@@ -0,0 +1,125 @@
+use code_pal::app::layout::{resize, ColumnWidth};
+use code_pal::config::{Config, LayoutConfig};
+use std::fs;
+use tui::layout::Direction;
+
+#[test]
+fn column_widths_are_written_as_in_the_config() {
+    let config = Config::parse("[layout]\nproject_columns = [\"auto\", \"25%\", \"30\"]").unwrap();
+    assert_eq!(
+        config.layout.project_columns,
+        vec![
+            ColumnWidth::Auto,
+            ColumnWidth::Percentage(25),
+            ColumnWidth::Length(30)
+        ]
+    );
+    assert!(Config::parse("[layout]\nproject_columns = [\"120%\"]").is_err());
+    assert!(Config::parse("[layout]\nproject_columns = [\"wide\"]").is_err());
+    for width in config.layout.project_columns {
+        assert_eq!(width.to_string().parse::<ColumnWidth>().unwrap(), width);
+    }
+}
+
+#[test]
+fn panes_stack_on_narrow_terminals_and_keep_a_minimum_share() {
+    let mut layout = LayoutConfig::default();
+    assert_eq!(layout.direction(79), Direction::Vertical);
+    assert_eq!(layout.direction(80), Direction::Horizontal);
+    assert_eq!(resize(35, 5), 40);
+    assert_eq!(resize(12, -5), 10);
+    assert_eq!(resize(88, 5), 90);
+
+    let width = layout.project_columns.pop().unwrap();
+    assert!(layout.validate().is_err());
+    layout.project_columns.push(width);
+    layout.list_width = 95;
+    assert!(layout.validate().is_err());
+    layout.stack_below = 100;
+    layout.reset();
+    assert!(layout.validate().is_ok());
+    assert_eq!(layout.list_width, LayoutConfig::default().list_width);
+    assert_eq!(layout.stack_below, 100);
+}
+
+#[test]
+fn saving_the_layout_leaves_the_rest_of_the_config_alone() {
+    let path = std::env::temp_dir().join(format!("code_pal_{}.toml", uuid::Uuid::new_v4()));
+    let original = "# my settings\n[layout]\nlist_width = 35\n\n[keys]\npreset = \"vim\"  # kept\n";
+    fs::write(&path, original).unwrap();
+    let layout = LayoutConfig {
+        list_width: 50,
+        ..LayoutConfig::default()
+    };
+    Config::save_layout(&path, &layout).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("# my settings\n[layout]\n"));
+    assert!(saved.contains("preset = \"vim\"  # kept"));
+    let config = Config::parse(&saved).unwrap();
+    assert_eq!(config.layout, layout);
+    assert_eq!(config.keys.preset.as_deref(), Some("vim"));
+
+    fs::write(&path, "[editing]\nmodal = true\n").unwrap();
+    Config::save_layout(&path, &layout).unwrap();
+    let config = Config::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(config.layout, layout);
+    assert!(config.editing.modal);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn saving_the_layout_keeps_its_comments_and_dotted_keys() {
+    let path = std::env::temp_dir().join(format!("code_pal_{}.toml", uuid::Uuid::new_v4()));
+    let layout = LayoutConfig {
+        list_width: 50,
+        ..LayoutConfig::default()
+    };
+    let original = "[keys.global]\nhelp = \"alt+[\"\n\n[layout]\n# the todo list\nlist_width = 35   # percent\nproject_columns = [\n  \"auto\",\n  \"40%\",\n  \"40%\",\n]\n\n[editing]\nmodal = true\n";
+    fs::write(&path, original).unwrap();
+    Config::save_layout(&path, &layout).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.contains("[layout]\n# the todo list\nlist_width = 50   # percent\n"));
+    assert_eq!(saved.matches("[layout]").count(), 1);
+    let config = Config::parse(&saved).unwrap();
+    assert_eq!(config.layout, layout);
+    assert!(config.editing.modal);
+
+    fs::write(
+        &path,
+        "layout.list_width = 35  # wide\n\n[editing]\nmodal = true\n",
+    )
+    .unwrap();
+    Config::save_layout(&path, &layout).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("layout.list_width = 50  # wide\nlayout.todo_height = 30\n"));
+    assert!(!saved.contains("[layout]"));
+    assert_eq!(Config::parse(&saved).unwrap().layout, layout);
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn saving_the_layout_keeps_an_inline_table_and_multi_line_strings() {
+    let path = std::env::temp_dir().join(format!("code_pal_{}.toml", uuid::Uuid::new_v4()));
+    let layout = LayoutConfig {
+        list_width: 50,
+        ..LayoutConfig::default()
+    };
+    fs::write(&path, "layout = { list_width = 35 }  # wide\n").unwrap();
+    Config::save_layout(&path, &layout).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with("layout = { list_width = 50, todo_height = 30"));
+    assert!(saved.ends_with("}  # wide\n"));
+    assert!(!saved.contains("[layout]"));
+    assert_eq!(Config::load(&path).unwrap().layout, layout);
+
+    let original = "[theme.colors]\nselection = \"\"\"\n[layout]\nlist_width = 1\"\"\"\nborder = '''\n# not a comment\n'''\n";
+    fs::write(&path, original).unwrap();
+    Config::save_layout(&path, &layout).unwrap();
+    let saved = fs::read_to_string(&path).unwrap();
+    assert!(saved.starts_with(original));
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.layout, layout);
+    assert_eq!(config.theme.colors["selection"], "[layout]\nlist_width = 1");
+    assert_eq!(config.theme.colors["border"], "# not a comment\n");
+    fs::remove_file(&path).unwrap();
+}
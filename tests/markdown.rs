@@ -0,0 +1,57 @@
+use code_pal::app::markdown::render;
+use code_pal::app::theme::Theme;
+use tui::style::Modifier;
+use tui::widgets::Text;
+
+/// The rendered spans as (text, modifier) pairs, line breaks left out.
+fn spans(markdown: &str) -> Vec<(String, Modifier)> {
+    render(markdown, &Theme::default())
+        .into_iter()
+        .filter_map(|text| match text {
+            Text::Raw(s) if s == "\n" => None,
+            Text::Raw(s) => Some((s.into_owned(), Modifier::empty())),
+            Text::Styled(s, style) => Some((s.into_owned(), style.modifier)),
+        })
+        .filter(|(s, _)| !s.is_empty())
+        .collect()
+}
+
+#[test]
+fn renders_headings_lists_and_checkboxes() {
+    let rendered = spans("# Plan\n- [x] write it\n- [ ] ship it\n* loose end\n2. second");
+    let texts: Vec<_> = rendered.iter().map(|(s, _)| s.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "Plan",
+            "[x] ",
+            "write it",
+            "[ ] ",
+            "ship it",
+            "• ",
+            "loose end",
+            "2. ",
+            "second"
+        ]
+    );
+    assert!(rendered[0].1.contains(Modifier::BOLD));
+    assert!(rendered[2].1.contains(Modifier::CROSSED_OUT));
+    assert!(!rendered[4].1.contains(Modifier::CROSSED_OUT));
+}
+
+#[test]
+fn leaves_code_blocks_and_inline_code_alone() {
+    let rendered = spans("run `cargo **test**` now\n```\n# not a heading\n```");
+    let texts: Vec<_> = rendered.iter().map(|(s, _)| s.as_str()).collect();
+    assert_eq!(
+        texts,
+        vec![
+            "run ",
+            "cargo **test**",
+            " now",
+            "```\n",
+            "  # not a heading\n",
+            "```\n"
+        ]
+    );
+}
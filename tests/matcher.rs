@@ -0,0 +1,30 @@
+use code_pal::app::matcher::{FuzzyMatcher, Matcher, SubstringMatcher};
+
+#[test]
+fn fuzzy_matches_subsequences_ignoring_case() {
+    let m = FuzzyMatcher.matches("api", "Billing-API").unwrap();
+    assert_eq!(m.positions, vec![8, 9, 10]);
+    assert!(FuzzyMatcher.matches("bapi", "Billing-API").is_some());
+    assert!(FuzzyMatcher.matches("ipa", "Billing-API").is_none());
+    assert!(FuzzyMatcher.matches("", "anything").is_some());
+}
+
+#[test]
+fn fuzzy_ranks_word_starts_and_runs_higher() {
+    let score = |candidate| FuzzyMatcher.matches("api", candidate).unwrap().score;
+    assert!(score("api-gateway") > score("Billing-API"));
+    assert!(score("Billing-API") > score("a-pricing-index"));
+    assert!(score("BillingApi") > score("capital"));
+}
+
+#[test]
+fn substring_requires_contiguous_match() {
+    assert_eq!(
+        SubstringMatcher
+            .matches("API", "billing-api")
+            .unwrap()
+            .positions,
+        vec![8, 9, 10]
+    );
+    assert!(SubstringMatcher.matches("bapi", "Billing-API").is_none());
+}
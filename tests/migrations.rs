@@ -0,0 +1,104 @@
+use chrono::NaiveDate;
+use code_pal::app::AppState;
+use code_pal::storage::{migrations, SCHEMA_VERSION};
+use code_pal::todo::{Priority, Status};
+use serde_json::json;
+use std::fs;
+use std::path::PathBuf;
+
+fn fixture(name: &str) -> (PathBuf, String) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name);
+    let json = fs::read_to_string(&path).unwrap();
+    (path, json)
+}
+
+fn load(name: &str) -> AppState {
+    let (path, json) = fixture(name);
+    AppState::from_json(&path, &json).unwrap()
+}
+
+#[test]
+fn upgrades_unversioned_store() {
+    let state = load("v0.json");
+    assert_eq!(state.schema_version, SCHEMA_VERSION);
+
+    let todos = state.todo_items.borrow();
+    assert_eq!(todos.len(), 2);
+    assert_eq!(todos[0].title, "write the release notes");
+    assert_eq!(todos[0].project_identifier, "");
+    assert_eq!(todos[0].notes.borrow()[0].content, "mention the new tabs");
+
+    let projects = state.projects.borrow();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "");
+    assert!(!projects[0].identifier.is_empty());
+}
+
+#[test]
+fn loads_version_1_store() {
+    let state = load("v1.json");
+    assert_eq!(state.schema_version, SCHEMA_VERSION);
+
+    let todos = state.todo_items.borrow();
+    assert_eq!(todos.len(), 1);
+    assert_eq!(
+        todos[0].project_identifier,
+        "3a4b5c6d-7e8f-4a0b-9c1d-2e3f4a5b6c7d"
+    );
+    assert_eq!(todos[0].description, vec!["draft in the wiki"]);
+    assert_eq!(todos[0].status, Status::Todo);
+    assert_eq!(todos[0].priority, Priority::Normal);
+    assert_eq!(todos[0].due, None);
+    assert_eq!(todos[0].branch, None);
+
+    let projects = state.projects.borrow();
+    assert_eq!(projects.len(), 1);
+    assert_eq!(projects[0].name, "code_pal");
+    assert_eq!(projects[0].dir_location, "~/src/code_pal");
+}
+
+#[test]
+fn loads_version_2_store() {
+    let state = load("v2.json");
+    assert_eq!(state.schema_version, SCHEMA_VERSION);
+
+    let todos = state.todo_items.borrow();
+    assert_eq!(todos.len(), 2);
+    assert_eq!(todos[0].status, Status::InProgress);
+    assert_eq!(todos[0].priority, Priority::High);
+    assert_eq!(todos[0].due, Some(NaiveDate::from_ymd(2020, 2, 14)));
+    assert!(todos[0].is_overdue(NaiveDate::from_ymd(2020, 2, 15)));
+    assert_eq!(todos[1].status, Status::Done);
+    assert_eq!(todos[1].priority, Priority::Low);
+    assert!(!todos[1].is_overdue(NaiveDate::from_ymd(2020, 2, 15)));
+    assert_eq!(todos[0].created_at, todos[0].updated_at);
+}
+
+#[test]
+fn upgraded_store_round_trips() {
+    let (path, _) = fixture("v0.json");
+    let state = load("v0.json");
+    let saved = serde_json::to_string(&state).unwrap();
+    let reloaded = AppState::from_json(&path, &saved).unwrap();
+    assert_eq!(reloaded.todo_items.borrow().len(), 2);
+    assert_eq!(
+        reloaded.projects.borrow()[0].identifier,
+        state.projects.borrow()[0].identifier
+    );
+}
+
+#[test]
+fn rejects_store_from_newer_version() {
+    let state = json!({ "schema_version": SCHEMA_VERSION + 1, "todo_items": [] });
+    assert!(migrations::migrate(state).is_err());
+}
+
+#[test]
+fn rejects_malformed_store() {
+    let (path, _) = fixture("v1.json");
+    assert!(AppState::from_json(&path, "{\"todo_items\": [").is_err());
+    assert!(migrations::migrate(json!([])).is_err());
+}
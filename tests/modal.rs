@@ -0,0 +1,81 @@
+mod common;
+
+use code_pal::app::keymap::Action;
+use code_pal::app::modal::{Modal, Mode, NormalKey};
+use code_pal::app::{App, CodePalAction};
+use common::{ctrl, key, press, type_text};
+use crossterm::event::KeyCode;
+
+#[test]
+fn normal_mode_commands_take_one_or_two_keys() {
+    let mut modal = Modal::default();
+    assert_eq!(modal.on_normal_key('j'), NormalKey::Action(Action::Next));
+    assert_eq!(modal.on_normal_key('g'), NormalKey::Pending);
+    assert_eq!(modal.pending(), Some('g'));
+    assert_eq!(modal.on_normal_key('g'), NormalKey::Action(Action::First));
+    assert_eq!(modal.on_normal_key('d'), NormalKey::Pending);
+    assert_eq!(modal.on_normal_key('k'), NormalKey::Action(Action::Up));
+    assert_eq!(modal.pending(), None);
+    assert_eq!(modal.on_normal_key('d'), NormalKey::Pending);
+    assert_eq!(
+        modal.on_normal_key('d'),
+        NormalKey::Action(Action::DeleteRow)
+    );
+    assert_eq!(
+        modal.on_normal_key('A'),
+        NormalKey::Insert(Some(KeyCode::End))
+    );
+    assert_eq!(modal.on_normal_key('q'), NormalKey::Ignored);
+}
+
+#[test]
+fn switching_modes_drops_a_pending_key() {
+    let mut modal = Modal::default();
+    assert_eq!(modal.mode, Mode::Normal);
+    modal.on_normal_key('d');
+    modal.set_mode(Mode::Insert);
+    assert_eq!(modal.pending(), None);
+    assert_eq!(modal.mode, Mode::Insert);
+}
+
+#[test]
+fn normal_mode_changes_the_list_and_insert_mode_types() {
+    let root = common::temp_dir();
+    let (mut app, receiver) = common::app(&root);
+    app.modal = Some(Modal::default());
+    let titles = |app: &App| -> Vec<String> {
+        let todos = app.app_state.todo_items.borrow();
+        todos.iter().map(|todo| todo.title.clone()).collect()
+    };
+    press(&mut app, &receiver, ctrl('a'));
+    assert_eq!(app.mode(), Some(Mode::Normal));
+
+    // Keys without a meaning in normal mode don't type into the row.
+    type_text(&mut app, &receiver, "qz");
+    assert_eq!(titles(&app), vec![""]);
+
+    type_text(&mut app, &receiver, "o");
+    assert_eq!(app.mode(), Some(Mode::Insert));
+    type_text(&mut app, &receiver, "fix it");
+    assert_eq!(titles(&app), vec!["", "fix it"]);
+    press(&mut app, &receiver, key(KeyCode::Esc));
+    assert_eq!(app.mode(), Some(Mode::Normal));
+
+    // Neither do the editing chords.
+    press(&mut app, &receiver, ctrl('w'));
+    assert_eq!(titles(&app), vec!["", "fix it"]);
+
+    type_text(&mut app, &receiver, "Oship");
+    assert_eq!(titles(&app), vec!["", "ship", "fix it"]);
+    press(&mut app, &receiver, key(KeyCode::Esc));
+    type_text(&mut app, &receiver, "dd");
+    assert_eq!(titles(&app), vec!["", "fix it"]);
+    type_text(&mut app, &receiver, "jdd");
+    assert_eq!(titles(&app), vec![""]);
+
+    // Esc in normal mode leaves the list.
+    assert_eq!(app.current_action, CodePalAction::AddToDoItem);
+    press(&mut app, &receiver, key(KeyCode::Esc));
+    assert_eq!(app.current_action, CodePalAction::None);
+    std::fs::remove_dir_all(root).unwrap_or_default();
+}
@@ -0,0 +1,58 @@
+mod common;
+
+use code_pal::app::ui_component::{TableEditor, UIEventProcessor};
+use code_pal::todo::todo::Project;
+use code_pal::util::TabsState;
+use crossterm::event::{KeyModifiers, MouseButton, MouseEvent};
+use tui::backend::TestBackend;
+use tui::layout::Rect;
+use tui::Terminal;
+
+fn click(column: u16, row: u16) -> MouseEvent {
+    MouseEvent::Down(MouseButton::Left, column, row, KeyModifiers::empty())
+}
+
+#[test]
+fn tabs_are_found_by_their_titles() {
+    let mut tabs = TabsState::new(vec!["Notes", "Projects", "Workspaces"]);
+    tabs.area = Rect::new(0, 0, 60, 3);
+    // " Notes " starts inside the border, then a divider, then " Projects ".
+    assert_eq!(tabs.title_at(0), None);
+    assert_eq!(tabs.title_at(1), Some(0));
+    assert_eq!(tabs.title_at(7), Some(0));
+    assert_eq!(tabs.title_at(8), None);
+    assert_eq!(tabs.title_at(9), Some(1));
+    assert_eq!(tabs.title_at(20), Some(2));
+    assert_eq!(tabs.title_at(40), None);
+}
+
+#[test]
+fn clicks_select_table_rows_below_the_headers() {
+    let mut table = common::project_table(30);
+    let mut terminal = Terminal::new(TestBackend::new(40, 14)).unwrap();
+    let area = Rect::new(0, 2, 40, 12);
+    let mut draw = |table: &mut TableEditor<Project>| common::draw(&mut terminal, table, area);
+    table.on_activate();
+    draw(&mut table);
+
+    // The border, the headers and the gap take the first three lines.
+    table.on_mouse(click(5, 4));
+    assert_eq!(table.current_selection, Some(0));
+    table.on_mouse(click(5, 5));
+    assert_eq!(table.current_selection, Some(0));
+    table.on_mouse(click(5, 7));
+    assert_eq!(table.current_selection, Some(2));
+    // The borders aren't rows.
+    table.on_mouse(click(0, 8));
+    table.on_mouse(click(5, 13));
+    assert_eq!(table.current_selection, Some(2));
+
+    for _ in 0..4 {
+        table.on_mouse(MouseEvent::ScrollDown(5, 7, KeyModifiers::empty()));
+    }
+    assert_eq!(table.current_selection, Some(14));
+    draw(&mut table);
+    assert_eq!(table.viewport.offset, 7);
+    table.on_mouse(click(5, 5));
+    assert_eq!(table.current_selection, Some(7));
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use chrono::NaiveDate;
+use code_pal::app::{App, CodePalAction, Event};
+use code_pal::git::{self, git};
+use common::{ctrl, key, press, type_text};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
+
+#[test]
+fn due_dates_can_be_typed() {
+    let root = common::temp_dir();
+    let (mut app, receiver) = common::app(&root);
+    let due = |app: &App| app.app_state.todo_items.borrow()[0].due;
+    let set_due = KeyEvent::new(KeyCode::Char('D'), KeyModifiers::ALT);
+    press(&mut app, &receiver, ctrl('a'));
+
+    press(&mut app, &receiver, set_due);
+    assert_eq!(app.current_action, CodePalAction::Due);
+    type_text(&mut app, &receiver, "2020-02-31");
+    press(&mut app, &receiver, key(KeyCode::Enter));
+    // Not a date, so it keeps asking.
+    assert_eq!(app.current_action, CodePalAction::Due);
+    assert_eq!(due(&app), None);
+
+    app.due_editor.text = String::from("2020-02-14");
+    press(&mut app, &receiver, key(KeyCode::Enter));
+    assert_eq!(app.current_action, CodePalAction::None);
+    assert_eq!(due(&app), Some(NaiveDate::from_ymd(2020, 2, 14)));
+
+    // The prompt starts from the date, clearing it removes it.
+    press(&mut app, &receiver, set_due);
+    assert_eq!(app.due_editor.text, "2020-02-14");
+    app.due_editor.text.clear();
+    press(&mut app, &receiver, key(KeyCode::Enter));
+    assert_eq!(due(&app), None);
+    app.on_undo();
+    assert_eq!(due(&app), Some(NaiveDate::from_ymd(2020, 2, 14)));
+    std::fs::remove_dir_all(root).unwrap_or_default();
+}
+
+#[test]
+fn branches_are_checked_before_they_are_stored() {
+    let root = common::temp_dir();
+    let (mut app, receiver) = common::app(&root);
+    let repo = root.join("repo");
+    std::fs::create_dir_all(&repo).unwrap();
+    git(&repo, &["init", "-q", "-b", "main"]).unwrap();
+    let author = ["-c", "user.name=Test", "-c", "user.email=test@example.com"];
+    let mut args = author.to_vec();
+    args.extend(&["commit", "-q", "--allow-empty", "-m", "Start"]);
+    git(&repo, &args).unwrap();
+    {
+        let mut projects = app.app_state.projects.borrow_mut();
+        projects[0].dir_location = repo.display().to_string();
+        app.app_state.todo_items.borrow_mut()[0].project_identifier =
+            projects[0].identifier.clone();
+    }
+    let branch = |app: &App| app.app_state.todo_items.borrow()[0].branch.clone();
+    press(&mut app, &receiver, ctrl('a'));
+    let name_branch = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::ALT);
+
+    press(&mut app, &receiver, name_branch);
+    assert_eq!(app.current_action, CodePalAction::Branch);
+    app.branch_editor.text = String::from("not a branch");
+    press(&mut app, &receiver, key(KeyCode::Enter));
+    assert_eq!(app.current_action, CodePalAction::Branch);
+    assert_eq!(branch(&app), None);
+
+    app.branch_editor.text = String::from("feature/login");
+    press(&mut app, &receiver, key(KeyCode::Enter));
+    assert_eq!(app.current_action, CodePalAction::None);
+    assert_eq!(branch(&app).as_deref(), Some("feature/login"));
+    // The checkout happens in the background and reports back.
+    while !app.message.as_deref().unwrap_or("").starts_with("Created") {
+        if let Event::Action(action) = receiver.recv_timeout(Duration::from_secs(10)).unwrap() {
+            app.on_action(action);
+        }
+    }
+    assert_eq!(
+        git::status(&repo).unwrap().branch.as_deref(),
+        Some("feature/login")
+    );
+    std::fs::remove_dir_all(root).unwrap_or_default();
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use code_pal::app::search::{search, SearchTarget};
+use code_pal::todo::todo::EditableStateItem;
+use code_pal::todo::{Note, Priority, Project, Status, TodoItem};
+use common::{project, todo};
+
+fn fixtures() -> (Vec<TodoItem>, Vec<Project>) {
+    let release = todo(
+        "Release the billing client",
+        Status::Todo,
+        Priority::Normal,
+        "",
+    );
+    release.notes.borrow_mut()[0] = Note::new(String::from(
+        "the changelog has to mention that the Billing-API moved to a new host",
+    ));
+    let docs = todo("write docs", Status::Todo, Priority::Normal, "");
+    let projects = vec![project(
+        "p1",
+        "billing-api",
+        "https://example.com/billing",
+        "~/src/billing",
+    )];
+    (vec![release, docs], projects)
+}
+
+#[test]
+fn ranks_titles_before_projects_before_notes() {
+    let (todos, projects) = fixtures();
+    let targets: Vec<_> = search("BILLING", &todos, &projects)
+        .into_iter()
+        .map(|result| result.target)
+        .collect();
+    assert_eq!(
+        targets,
+        vec![
+            SearchTarget::Todo(0),
+            SearchTarget::Project(0, 0),
+            SearchTarget::Note(0, 0)
+        ]
+    );
+}
+
+#[test]
+fn requires_every_term_and_shows_context() {
+    let (todos, projects) = fixtures();
+    let results = search("moved host", &todos, &projects);
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].target, SearchTarget::Note(0, 0));
+    assert!(results[0]
+        .label
+        .starts_with("Note in Release the billing client: ..."));
+    assert!(results[0]
+        .label
+        .ends_with("Billing-API moved to a new host"));
+
+    assert!(search("   ", &todos, &projects).is_empty());
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use code_pal::app::AppState;
+use code_pal::storage::{SqliteStore, Storage};
+use code_pal::todo::todo::{EditableRowItem, EditableStateItem};
+use code_pal::todo::{Note, Project, TodoItem};
+use rusqlite::{params, Connection, NO_PARAMS};
+use std::fs;
+use std::path::Path;
+
+/// Two todos with two notes between them, and two projects.
+fn state() -> AppState {
+    let state = AppState::empty();
+    state.todo_items.borrow_mut()[0].title = String::from("write docs");
+    let mut todo = TodoItem::new(String::from("fix the build"));
+    todo.notes.borrow_mut()[0].content = String::from("flaky on windows");
+    todo.notes
+        .borrow_mut()
+        .push(Note::new(String::from("only in release")));
+    state.todo_items.borrow_mut().push(todo);
+    state.projects.borrow_mut()[0].name = String::from("code_pal");
+    state.projects.borrow_mut().push(Project::new(vec![
+        String::from("tui"),
+        String::new(),
+        String::new(),
+    ]));
+    state
+}
+
+fn titles(state: &AppState) -> Vec<String> {
+    let todos = state.todo_items.borrow();
+    todos.iter().map(|todo| todo.title.clone()).collect()
+}
+
+fn notes(state: &AppState, todo: usize) -> Vec<String> {
+    let todos = state.todo_items.borrow();
+    let notes = todos[todo].notes.borrow();
+    notes.iter().map(|note| note.content.clone()).collect()
+}
+
+fn count(path: &Path, table: &str) -> i64 {
+    let connection = Connection::open(path).unwrap();
+    connection
+        .query_row(
+            &format!("SELECT COUNT(*) FROM {}", table),
+            NO_PARAMS,
+            |row| row.get(0),
+        )
+        .unwrap()
+}
+
+#[test]
+fn states_round_trip() {
+    let dir = common::temp_dir();
+    let path = dir.join("todos.db");
+    let state = state();
+    SqliteStore::open(&path).unwrap().save(&state).unwrap();
+
+    let loaded = SqliteStore::open(&path).unwrap().load().unwrap();
+    assert_eq!(titles(&loaded), vec!["write docs", "fix the build"]);
+    assert_eq!(
+        notes(&loaded, 1),
+        vec!["flaky on windows", "only in release"]
+    );
+    let projects = loaded.projects.borrow();
+    assert_eq!(
+        projects[0].identifier,
+        state.projects.borrow()[0].identifier
+    );
+    assert_eq!(projects[1].name, "tui");
+    assert_eq!(
+        serde_json::to_value(&loaded).unwrap(),
+        serde_json::to_value(&state).unwrap()
+    );
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn saves_only_write_the_rows_that_changed() {
+    let dir = common::temp_dir();
+    let path = dir.join("todos.db");
+    let mut store = SqliteStore::open(&path).unwrap();
+    let state = store.load().unwrap();
+    state.todo_items.borrow_mut()[0].title = String::from("first");
+    state
+        .todo_items
+        .borrow_mut()
+        .push(TodoItem::new(String::from("second")));
+    store.save(&state).unwrap();
+
+    // Changed behind the store's back, so that rewriting the row shows.
+    let second = state.todo_items.borrow()[1].identifier.clone();
+    let connection = Connection::open(&path).unwrap();
+    let data: String = connection
+        .query_row(
+            "SELECT data FROM todo_items WHERE identifier = ?1",
+            params![second],
+            |row| row.get(0),
+        )
+        .unwrap();
+    connection
+        .execute(
+            "UPDATE todo_items SET data = ?1 WHERE identifier = ?2",
+            params![data.replace("second", "tampered"), second],
+        )
+        .unwrap();
+
+    state.todo_items.borrow_mut()[0].title = String::from("first, edited");
+    store.save(&state).unwrap();
+    let loaded = SqliteStore::open(&path).unwrap().load().unwrap();
+    assert_eq!(titles(&loaded), vec!["first, edited", "tampered"]);
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn removed_todos_notes_and_projects_are_deleted() {
+    let dir = common::temp_dir();
+    let path = dir.join("todos.db");
+    let mut store = SqliteStore::open(&path).unwrap();
+    let state = state();
+    store.save(&state).unwrap();
+    assert_eq!(count(&path, "todo_items"), 2);
+    assert_eq!(count(&path, "notes"), 3);
+    assert_eq!(count(&path, "projects"), 2);
+
+    state.todo_items.borrow()[1].notes.borrow_mut().remove(0);
+    state.projects.borrow_mut().remove(0);
+    store.save(&state).unwrap();
+    assert_eq!(count(&path, "notes"), 2);
+    assert_eq!(count(&path, "projects"), 1);
+    let loaded = SqliteStore::open(&path).unwrap().load().unwrap();
+    assert_eq!(notes(&loaded, 1), vec!["only in release"]);
+    assert_eq!(loaded.projects.borrow()[0].name, "tui");
+
+    // A todo goes together with its notes.
+    state.todo_items.borrow_mut().remove(1);
+    store.save(&state).unwrap();
+    assert_eq!(count(&path, "todo_items"), 1);
+    assert_eq!(count(&path, "notes"), 1);
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
+
+#[test]
+fn unversioned_stores_are_migrated_on_load() {
+    let dir = common::temp_dir();
+    let path = dir.join("todos.db");
+    drop(SqliteStore::open(&path).unwrap());
+    let connection = Connection::open(&path).unwrap();
+    connection
+        .execute_batch(
+            "INSERT INTO meta (key, value) VALUES ('schema_version', '0');
+            INSERT INTO todo_items VALUES ('t1', '', 0, '{\"title\":\"write the release notes\",\"description\":[]}');
+            INSERT INTO notes VALUES ('n1', 't1', 0, '{\"content\":\"mention the new tabs\"}');",
+        )
+        .unwrap();
+
+    let mut store = SqliteStore::open(&path).unwrap();
+    let state = store.load().unwrap();
+    {
+        let todos = state.todo_items.borrow();
+        assert_eq!(todos[0].title, "write the release notes");
+        assert_eq!(todos[0].project_identifier, "");
+        assert_eq!(todos[0].branch, None);
+        assert_eq!(notes(&state, 0), vec!["mention the new tabs"]);
+    }
+    // Saving writes the upgraded rows and version.
+    store.save(&state).unwrap();
+    let version: String = connection
+        .query_row(
+            "SELECT value FROM meta WHERE key = 'schema_version'",
+            NO_PARAMS,
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(version, code_pal::storage::SCHEMA_VERSION.to_string());
+    let loaded = SqliteStore::open(&path).unwrap().load().unwrap();
+    assert_eq!(titles(&loaded), vec!["write the release notes"]);
+    fs::remove_dir_all(dir).unwrap_or_default();
+}
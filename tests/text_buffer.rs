@@ -0,0 +1,76 @@
+use code_pal::app::keymap::Action;
+use code_pal::app::text_buffer::{Edit, KeyResult, TextBuffer, TextInput};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+fn key(code: KeyCode) -> KeyEvent {
+    KeyEvent::new(code, KeyModifiers::empty())
+}
+
+fn press(buffer: &mut TextBuffer, text: &mut String, input: impl Into<TextInput>) {
+    buffer.attach((0, 0), text);
+    if let KeyResult::Edited(edit, _) = buffer.on_input(text, input) {
+        edit.apply(text);
+    }
+}
+
+#[test]
+fn inserts_and_deletes_at_the_cursor() {
+    let mut text = String::from("helo world");
+    let mut buffer = TextBuffer::new();
+    for _ in 0..7 {
+        press(&mut buffer, &mut text, key(KeyCode::Left));
+    }
+    press(&mut buffer, &mut text, key(KeyCode::Char('l')));
+    assert_eq!(text, "hello world");
+    press(&mut buffer, &mut text, key(KeyCode::End));
+    press(&mut buffer, &mut text, key(KeyCode::Char('!')));
+    press(&mut buffer, &mut text, key(KeyCode::Home));
+    press(&mut buffer, &mut text, key(KeyCode::Delete));
+    assert_eq!(text, "ello world!");
+}
+
+#[test]
+fn moves_and_deletes_whole_graphemes() {
+    // "e" followed by a combining acute accent is a single grapheme.
+    let mut text = String::from("cafe\u{301}s");
+    let mut buffer = TextBuffer::new();
+    press(&mut buffer, &mut text, key(KeyCode::Left));
+    press(&mut buffer, &mut text, key(KeyCode::Left));
+    assert_eq!(buffer.cursor(), 3);
+    press(&mut buffer, &mut text, key(KeyCode::Delete));
+    assert_eq!(text, "cafs");
+    let mut buffer = TextBuffer::new();
+    let mut text = String::from("cafe\u{301}");
+    press(&mut buffer, &mut text, key(KeyCode::Backspace));
+    assert_eq!(text, "caf");
+}
+
+#[test]
+fn jumps_and_deletes_words_and_replaces_selections() {
+    let mut text = String::from("fix the login-page");
+    let mut buffer = TextBuffer::new();
+    let ctrl = |code| KeyEvent::new(code, KeyModifiers::CONTROL);
+    press(&mut buffer, &mut text, ctrl(KeyCode::Left));
+    assert_eq!(buffer.cursor(), 14);
+    press(&mut buffer, &mut text, key(KeyCode::End));
+    press(&mut buffer, &mut text, Action::DeleteWord);
+    assert_eq!(text, "fix the login-");
+    press(&mut buffer, &mut text, Action::DeleteChar);
+    assert_eq!(text, "fix the login");
+    press(&mut buffer, &mut text, key(KeyCode::Home));
+    let shift = KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL | KeyModifiers::SHIFT);
+    press(&mut buffer, &mut text, shift);
+    assert_eq!(buffer.selected_text(&text), Some("fix "));
+    buffer.attach((0, 0), &text);
+    assert_eq!(
+        buffer.on_event(&text, key(KeyCode::Char('F'))),
+        KeyResult::Edited(
+            Edit {
+                position: 0,
+                removed: String::from("fix "),
+                inserted: String::from("F"),
+            },
+            code_pal::app::history::EditKind::Other
+        )
+    );
+}
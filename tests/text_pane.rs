@@ -0,0 +1,15 @@
+use code_pal::app::text_pane::wrap;
+
+#[test]
+fn wraps_lines_at_spaces_and_keeps_line_breaks() {
+    let text = "the quick brown fox\n\njumps";
+    let rows: Vec<_> = wrap(text, 10).into_iter().map(|row| &text[row]).collect();
+    assert_eq!(rows, vec!["the quick ", "brown fox", "", "jumps"]);
+}
+
+#[test]
+fn breaks_words_longer_than_the_width() {
+    let text = "abcdefghij";
+    let rows: Vec<_> = wrap(text, 4).into_iter().map(|row| &text[row]).collect();
+    assert_eq!(rows, vec!["abcd", "efgh", "ij"]);
+}
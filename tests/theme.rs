@@ -0,0 +1,67 @@
+use code_pal::app::theme::{parse_color, Theme, THEME_NAMES};
+use code_pal::config::Config;
+use tui::style::Color;
+
+fn theme(toml: &str, flag: Option<&str>, no_color: bool) -> Result<Theme, failure::Error> {
+    Theme::from_config(&Config::parse(toml)?.theme, flag, no_color)
+}
+
+#[test]
+fn the_flag_wins_over_the_config_which_wins_over_no_color() {
+    let config = "[theme]\nname = \"light\"";
+    assert_eq!(
+        theme(config, Some("solarized"), true).unwrap(),
+        Theme::solarized()
+    );
+    assert_eq!(theme(config, None, true).unwrap(), Theme::light());
+    assert_eq!(theme("", None, true).unwrap(), Theme::monochrome());
+    assert_eq!(theme("", None, false).unwrap(), Theme::dark());
+    assert!(theme("", Some("neon"), false).is_err());
+    for name in THEME_NAMES.iter() {
+        assert!(Theme::named(name).is_ok());
+    }
+}
+
+#[test]
+fn monochrome_has_no_colors() {
+    let t = Theme::monochrome();
+    let styles = [
+        t.tabs,
+        t.selected_tab,
+        t.title,
+        t.selection,
+        t.header,
+        t.group,
+        t.accent,
+        t.code,
+        t.marker,
+        t.success,
+        t.muted,
+        t.warning,
+        t.error,
+        t.in_progress,
+        t.blocked,
+        t.done,
+        t.quote,
+        t.normal_mode,
+        t.insert_mode,
+    ];
+    for style in styles.iter() {
+        assert_eq!((style.fg, style.bg), (Color::Reset, Color::Reset));
+    }
+}
+
+#[test]
+fn colors_of_the_config_replace_those_of_the_palette() {
+    let config = "[theme.colors]\nselection = \"#d33682\"\nerror = \"light_red\"";
+    let t = theme(config, None, false).unwrap();
+    assert_eq!(t.selection.fg, Color::Rgb(0xd3, 0x36, 0x82));
+    assert_eq!(t.selection.modifier, Theme::dark().selection.modifier);
+    assert_eq!(t.error.fg, Color::LightRed);
+    assert!(theme("[theme.colors]\nborder = \"red\"", None, false).is_err());
+
+    assert_eq!(parse_color("DARK_GRAY").unwrap(), Color::DarkGray);
+    assert_eq!(parse_color("208").unwrap(), Color::Indexed(208));
+    assert!(parse_color("#12345").is_err());
+    assert!(parse_color("mauve").is_err());
+}
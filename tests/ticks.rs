@@ -0,0 +1,40 @@
+mod common;
+
+use std::time::Duration;
+
+#[test]
+fn ticks_only_matter_while_something_waits_for_them() {
+    let root = common::temp_dir();
+    let (mut app, _receiver) = common::app(&root);
+    assert!(app.is_idle());
+    assert!(!app.on_tick());
+
+    app.autosave_delay = Some(Duration::from_secs(0));
+    app.mark_dirty();
+    assert!(!app.is_idle());
+    // Saving clears the changes and says so, until the message expires.
+    assert!(app.on_tick());
+    assert!(!app.dirty);
+    assert_eq!(app.message.as_deref(), Some("Saved"));
+    assert!(!app.is_idle());
+    assert!(!app.on_tick());
+
+    app.message = None;
+    assert!(app.is_idle());
+
+    // The repositories shown are looked at again every few seconds.
+    app.app_state.projects.borrow_mut()[0].dir_location = root.display().to_string();
+    assert!(app.is_idle());
+    app.tabs.index = 1;
+    assert!(!app.is_idle());
+    app.tabs.index = 0;
+    {
+        let identifier = app.app_state.projects.borrow()[0].identifier.clone();
+        let mut todos = app.app_state.todo_items.borrow_mut();
+        todos[0].project_identifier = identifier;
+        todos[0].branch = Some(String::from("login"));
+    }
+    app.todo_items.current_selection = Some(0);
+    assert!(!app.is_idle());
+    std::fs::remove_dir_all(root).unwrap_or_default();
+}
@@ -0,0 +1,25 @@
+use chrono::NaiveDate;
+use code_pal::todo::todo::EditableStateItem;
+use code_pal::todo::TodoItem;
+
+#[test]
+fn due_dates_are_typed_as_iso_dates() {
+    assert_eq!(
+        TodoItem::parse_due(" 2020-02-14 "),
+        Ok(Some(NaiveDate::from_ymd(2020, 2, 14)))
+    );
+    assert_eq!(TodoItem::parse_due(""), Ok(None));
+    assert!(TodoItem::parse_due("2020-02-30").is_err());
+    assert!(TodoItem::parse_due("tomorrow").is_err());
+}
+
+#[test]
+fn branches_are_named_after_the_title_without_tags() {
+    let branch = |title: &str| TodoItem::new(title.to_string()).suggested_branch();
+    assert_eq!(branch("fix the #bug in save"), "fix-the-in-save");
+    assert_eq!(branch("#bug: crash on quit"), "crash-on-quit");
+    assert_eq!(
+        branch("Support user's OAuth2.0 login"),
+        "support-user-s-oauth2-0-login"
+    );
+}
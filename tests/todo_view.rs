@@ -0,0 +1,69 @@
+mod common;
+
+use code_pal::app::todo_view::{SortMode, TodoFilter, TodoView};
+use code_pal::app::ui_component::ViewRow;
+use code_pal::todo::{Priority, Project, Status, TodoItem};
+use common::{project, todo};
+
+fn fixtures() -> (Vec<TodoItem>, Vec<Project>) {
+    let projects = vec![project("p1", "code_pal", "", "")];
+    let todos = vec![
+        todo("fix the #bug in save", Status::Todo, Priority::Normal, "p1"),
+        todo("write docs", Status::Done, Priority::Low, ""),
+        todo("#bug: crash on quit", Status::Blocked, Priority::High, ""),
+    ];
+    (todos, projects)
+}
+
+fn items(rows: &[ViewRow]) -> Vec<usize> {
+    rows.iter()
+        .filter_map(|row| match row {
+            ViewRow::Item(i) => Some(*i),
+            ViewRow::Group(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn tags_come_from_the_title() {
+    let (todos, _) = fixtures();
+    assert_eq!(todos[0].tags(), vec!["bug"]);
+    assert_eq!(todos[2].tags(), vec!["bug"]);
+    assert!(todos[1].tags().is_empty());
+}
+
+#[test]
+fn filter_matches_every_term() {
+    let (todos, projects) = fixtures();
+    let mut view = TodoView::default();
+    view.set_query("#bug status:open project:pal");
+    assert_eq!(items(&view.rows(&todos, &projects, None)), vec![0]);
+
+    view.set_query("CRASH");
+    assert_eq!(items(&view.rows(&todos, &projects, None)), vec![2]);
+    assert_eq!(items(&view.rows(&todos, &projects, Some(1))), vec![1, 2]);
+
+    assert!(TodoFilter::parse("").is_empty());
+}
+
+#[test]
+fn sorts_and_groups_by_underlying_index() {
+    let (todos, projects) = fixtures();
+    let mut view = TodoView {
+        sort: SortMode::Priority,
+        ..TodoView::default()
+    };
+    assert_eq!(items(&view.rows(&todos, &projects, None)), vec![2, 0, 1]);
+
+    view.group_by_project = true;
+    assert_eq!(
+        view.rows(&todos, &projects, None),
+        vec![
+            ViewRow::Group(String::from("code_pal")),
+            ViewRow::Item(0),
+            ViewRow::Group(String::from("No project")),
+            ViewRow::Item(2),
+            ViewRow::Item(1),
+        ]
+    );
+}
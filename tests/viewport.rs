@@ -0,0 +1,73 @@
+mod common;
+
+use code_pal::app::keymap::Action;
+use code_pal::app::ui_component::{TableEditor, UIEventProcessor};
+use code_pal::app::viewport::{Scrollbar, Viewport};
+use code_pal::todo::todo::Project;
+use tui::backend::TestBackend;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::Terminal;
+
+#[test]
+fn viewport_moves_only_to_keep_the_selection_in_view() {
+    let mut viewport = Viewport::default();
+    viewport.follow(5, 20, Some((3, 4)));
+    assert_eq!(viewport.offset, 0);
+    viewport.follow(5, 20, Some((7, 8)));
+    assert_eq!(viewport.offset, 3);
+    // Moving back up within the view leaves it where it is.
+    viewport.follow(5, 20, Some((4, 5)));
+    assert_eq!(viewport.offset, 3);
+    viewport.follow(5, 20, Some((1, 2)));
+    assert_eq!(viewport.offset, 1);
+    // A row taller than the pane shows from its first line.
+    viewport.follow(5, 20, Some((10, 18)));
+    assert_eq!(viewport.offset, 10);
+    // Shrinking the list pulls the view back.
+    viewport.follow(5, 12, None);
+    assert_eq!(viewport.offset, 7);
+    assert_eq!(viewport.line_at(2), Some(9));
+    assert_eq!(viewport.line_at(5), None);
+
+    let scrollbar = |offset, len| Scrollbar {
+        viewport: Viewport { offset, height: 10 },
+        len,
+        style: Style::default(),
+    };
+    assert_eq!(scrollbar(0, 10).thumb(10), None);
+    assert_eq!(scrollbar(0, 40).thumb(10), Some((0, 2)));
+    assert_eq!(scrollbar(30, 40).thumb(10), Some((8, 2)));
+    assert_eq!(scrollbar(15, 40).thumb(10), Some((4, 2)));
+}
+
+#[test]
+fn table_pages_and_keeps_the_selected_row_drawn() {
+    let mut table = common::project_table(30);
+    let mut terminal = Terminal::new(TestBackend::new(40, 12)).unwrap();
+    let area = Rect::new(0, 0, 40, 12);
+    let mut draw = |table: &mut TableEditor<Project>| common::draw(&mut terminal, table, area);
+    table.on_activate();
+    draw(&mut table);
+    // 12 lines less the borders, the headers and the gap below them.
+    assert_eq!(table.viewport.height, 8);
+
+    table.on_action(Action::PageDown);
+    table.on_action(Action::PageDown);
+    assert_eq!(table.current_selection, Some(16));
+    let lines = draw(&mut table);
+    assert_eq!(table.viewport.offset, 9);
+    assert!(lines.iter().any(|line| line.contains(">project 16")));
+
+    table.on_action(Action::Last);
+    draw(&mut table);
+    assert_eq!(table.current_selection, Some(29));
+    assert_eq!(table.viewport.offset, 22);
+
+    table.on_action(Action::PageUp);
+    table.on_action(Action::First);
+    let lines = draw(&mut table);
+    assert_eq!(table.current_selection, Some(0));
+    assert_eq!(table.viewport.offset, 0);
+    assert!(lines[3].contains(">project 0"));
+}